@@ -27,6 +27,7 @@ fn bench_path_insert(c: &mut Criterion) {
             })
         })
         .bench_function("ntex_router_path", |b| {
+            #[allow(deprecated)]
             let mut router = NtexRouter::<usize>::build();
             b.iter(|| {
                 for (i, r) in ROUTES_WITH_BRACES.iter().enumerate() {
@@ -50,6 +51,30 @@ fn bench_path_insert(c: &mut Criterion) {
                 }
             })
         })
+        .bench_function("path_tree_build", |b| {
+            let mut routes = ROUTES_WITH_COLON
+                .iter()
+                .copied()
+                .zip(0..)
+                .collect::<Vec<_>>();
+            routes.sort_unstable();
+            b.iter(|| {
+                let mut tree = PathTree::new();
+                for (r, i) in &routes {
+                    tree.insert(r, *i);
+                }
+                tree
+            })
+        })
+        .bench_function("path_tree_from_sorted", |b| {
+            let mut routes = ROUTES_WITH_COLON
+                .iter()
+                .copied()
+                .zip(0..)
+                .collect::<Vec<_>>();
+            routes.sort_unstable();
+            b.iter(|| PathTree::from_sorted(routes.iter().copied()))
+        })
         .bench_function("matchit_insert", |b| {
             let mut matcher = MatchitRouter::new();
             b.iter(|| {
@@ -100,10 +125,12 @@ fn bench_path_find(c: &mut Criterion) {
             })
         })
         .bench_function("ntex_router_recognize", |b| {
+            #[allow(deprecated)]
             let mut router = NtexRouter::<usize>::build();
             for (i, r) in ROUTES_WITH_BRACES.iter().enumerate() {
                 router.path(*r, i);
             }
+            #[allow(deprecated)]
             let router = router.finish();
             b.iter(|| {
                 for (i, r) in ROUTES_URLS.iter().enumerate() {
@@ -137,6 +164,32 @@ fn bench_path_find(c: &mut Criterion) {
                 }
             })
         })
+        .bench_function("path_tree_frozen_find", |b| {
+            let mut tree: PathTree<usize> = PathTree::new();
+            for (i, r) in ROUTES_WITH_COLON.iter().enumerate() {
+                tree.insert(r, i);
+            }
+            let tree = tree.freeze();
+            b.iter(|| {
+                for (i, r) in ROUTES_URLS.iter().enumerate() {
+                    let n = tree.find(r).unwrap();
+                    assert_eq!(*n.0, i);
+                }
+            })
+        })
+        .bench_function("path_tree_frozen_find_without_jump_tables", |b| {
+            let mut tree: PathTree<usize> = PathTree::new();
+            for (i, r) in ROUTES_WITH_COLON.iter().enumerate() {
+                tree.insert(r, i);
+            }
+            let tree = tree.freeze().without_jump_tables();
+            b.iter(|| {
+                for (i, r) in ROUTES_URLS.iter().enumerate() {
+                    let n = tree.find(r).unwrap();
+                    assert_eq!(*n.0, i);
+                }
+            })
+        })
         .bench_function("matchit_at", |b| {
             let mut matcher = MatchitRouter::new();
             for (i, r) in ROUTES_WITH_BRACES.iter().enumerate() {
@@ -180,5 +233,160 @@ fn bench_path_find(c: &mut Criterion) {
     group.finish();
 }
 
-criterion_group!(benches, bench_path_insert, bench_path_find);
+fn bench_path_scan(c: &mut Criterion) {
+    let mut group = c.benchmark_group("path_scan");
+
+    let routes = [
+        "/static/:path*/:file.:ext",
+        "/assets/:path+.min.:ext",
+        "/:org/:repo/blob/:ref/*",
+        "/:org/:repo/archive/:name-:version.tar.gz",
+        "/:org/:repo/releases/download/:tag/:filename.:ext",
+    ];
+    let segment = "abcdefghijklmnopqrstuvwxyz0123456789";
+    let deep = [segment; 16].join("/");
+    let urls = [
+        format!("/static/{deep}/app.bundle.js"),
+        format!("/assets/{deep}.min.css"),
+        format!("/viz-rs/path-tree/blob/main/{deep}"),
+        format!("/viz-rs/path-tree/archive/{segment}{segment}-{segment}.tar.gz"),
+        format!("/viz-rs/path-tree/releases/download/v0.8.3/{segment}{segment}.tar.gz"),
+    ];
+
+    group
+        .bench_function("path_tree_find", |b| {
+            let mut tree: PathTree<usize> = PathTree::new();
+            for (i, r) in routes.iter().enumerate() {
+                tree.insert(r, i);
+            }
+            b.iter(|| {
+                for (i, r) in urls.iter().enumerate() {
+                    let n = tree.find(r).unwrap();
+                    assert_eq!(*n.0, i);
+                }
+            })
+        })
+        .bench_function("path_tree_frozen_find", |b| {
+            let mut tree: PathTree<usize> = PathTree::new();
+            for (i, r) in routes.iter().enumerate() {
+                tree.insert(r, i);
+            }
+            let tree = tree.freeze();
+            b.iter(|| {
+                for (i, r) in urls.iter().enumerate() {
+                    let n = tree.find(r).unwrap();
+                    assert_eq!(*n.0, i);
+                }
+            })
+        })
+        .sample_size(20);
+
+    group.finish();
+}
+
+fn bench_path_fanout(c: &mut Criterion) {
+    let mut group = c.benchmark_group("path_fanout");
+
+    // 26 children at the root and at each first letter, past the jump table threshold
+    let routes = ('a'..='z')
+        .flat_map(|a| ('a'..='z').map(move |b| format!("/{a}/{b}{b}/:id")))
+        .collect::<Vec<_>>();
+    let paths = routes
+        .iter()
+        .map(|r| r.replace(":id", "42"))
+        .collect::<Vec<_>>();
+
+    let mut tree: PathTree<usize> = PathTree::new();
+    for (i, r) in routes.iter().enumerate() {
+        tree.insert(r, i);
+    }
+    let frozen = tree.clone().freeze();
+    let binary = frozen.clone().without_jump_tables();
+
+    group
+        .bench_function("path_tree_find", |b| {
+            b.iter(|| {
+                for (i, r) in paths.iter().enumerate() {
+                    assert_eq!(*tree.find(r).unwrap().0, i);
+                }
+            })
+        })
+        .bench_function("path_tree_frozen_find", |b| {
+            b.iter(|| {
+                for (i, r) in paths.iter().enumerate() {
+                    assert_eq!(*frozen.find(r).unwrap().0, i);
+                }
+            })
+        })
+        .bench_function("path_tree_frozen_find_without_jump_tables", |b| {
+            b.iter(|| {
+                for (i, r) in paths.iter().enumerate() {
+                    assert_eq!(*binary.find(r).unwrap().0, i);
+                }
+            })
+        })
+        .sample_size(20);
+
+    group.finish();
+}
+
+fn bench_path_batch(c: &mut Criterion) {
+    let mut group = c.benchmark_group("path_batch");
+
+    // mostly distinct and sorted, like a day of access logs: the params vary, the static
+    // prefixes are shared
+    let mut paths = ROUTES_WITH_COLON
+        .iter()
+        .flat_map(|r| {
+            (0..8).map(move |i| {
+                r.split('/')
+                    .map(|s| {
+                        if s.contains(':') {
+                            format!("{}{i}", s.replace(':', ""))
+                        } else {
+                            s.to_string()
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join("/")
+            })
+        })
+        .collect::<Vec<_>>();
+    paths.sort_unstable();
+    paths.dedup();
+    let paths = paths.iter().map(String::as_str).collect::<Vec<_>>();
+
+    let mut tree: PathTree<usize> = PathTree::new();
+    for (i, r) in ROUTES_WITH_COLON.iter().enumerate() {
+        tree.insert(r, i);
+    }
+
+    group
+        .bench_function("path_tree_find", |b| {
+            b.iter(|| {
+                paths
+                    .iter()
+                    .map(|r| tree.find(r).map(|(_, p)| (*p.id, p.raws)))
+                    .collect::<Vec<_>>()
+            })
+        })
+        .bench_function("path_tree_find_many", |b| {
+            b.iter(|| tree.find_many(paths.iter().copied()))
+        })
+        .bench_function("path_tree_classify", |b| {
+            b.iter(|| tree.classify(paths.iter().copied()))
+        })
+        .sample_size(20);
+
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_path_insert,
+    bench_path_find,
+    bench_path_scan,
+    bench_path_fanout,
+    bench_path_batch
+);
 criterion_main!(benches);
@@ -0,0 +1,164 @@
+use alloc::vec::Vec;
+
+use crate::{node::RadixNode, parse, parser, statics::static_bytes, PathTree, Piece};
+
+/// Tags the node values of the aliases, the untagged ones are the route ids.
+pub(crate) const ALIAS: usize = 1 << (usize::BITS - 1);
+
+/// Tags the node values of the variants, the patterns expanded from the groups of a route.
+pub(crate) const VARIANT: usize = 1 << (usize::BITS - 2);
+
+impl<T> PathTree<T> {
+    /// Inserts a pattern resolving to the route of the id, and returns the index of the alias.
+    ///
+    /// The alias matches the paths with its own parameters and [`Path::alias`] tells it apart,
+    /// while [`PathTree::url_for`] still generates the pattern of the route. It returns `None`
+    /// if no route has the id or the pattern is a route itself, or one of its variants, an
+    /// alias inserted again resolves to the new route.
+    ///
+    /// The groups are not expanded, a pattern with groups returns `None` too, each of its
+    /// patterns can be inserted as an alias of its own.
+    ///
+    /// [`Path::alias`]: crate::Path::alias
+    pub fn insert_alias(&mut self, path: &str, id: usize) -> Option<usize> {
+        if id >= self.routes.len() || parser::has_groups(path, self.optional_groups) {
+            return None;
+        }
+
+        self.insert_tagged(parse(path), id, ALIAS)
+    }
+
+    /// Inserts a pattern expanded from the groups of the route of the id, see
+    /// [`PathTree::insert`].
+    ///
+    /// It is left to the route or the alias of the same pattern, a variant inserted again
+    /// resolves to the new route.
+    pub(crate) fn insert_variant(&mut self, pieces: Vec<Piece>, id: usize) -> Option<usize> {
+        self.insert_tagged(pieces, id, VARIANT)
+    }
+
+    /// Inserts the pattern of an alias or a variant by the tag, and returns its index.
+    fn insert_tagged(&mut self, pieces: Vec<Piece>, id: usize, tag: usize) -> Option<usize> {
+        let patterns = if tag == ALIAS {
+            &self.aliases
+        } else {
+            &self.variants
+        };
+        let index = match self.node.get(&pieces) {
+            Some(slot) if slot & tag == 0 => return None,
+            Some(slot) => slot & !tag,
+            None => patterns.len(),
+        };
+
+        let node = self.node.insert_pieces(&pieces);
+        node.value = Some(tag | index);
+
+        if let Some(prefilter) = &mut self.prefilter {
+            prefilter.insert(&pieces);
+        }
+
+        // fully static patterns are also indexed when the walk reaches them
+        if let Some(bytes) = static_bytes(&pieces) {
+            if self
+                .node
+                .find(&bytes)
+                .is_some_and(|(i, _)| *i == tag | index)
+            {
+                self.statics.insert(&bytes, tag | index);
+            }
+        }
+
+        let patterns = if tag == ALIAS {
+            &mut self.aliases
+        } else {
+            &mut self.variants
+        };
+        if index == patterns.len() {
+            patterns.push((id, pieces));
+        } else {
+            patterns[index] = (id, pieces);
+        }
+        Some(index)
+    }
+
+    /// Returns the pattern of the route, or of its first variant `f` accepts if the route's is
+    /// not, e.g. `/archive/:year` of `/archive(/:year(/:month))` for one param.
+    pub(crate) fn variant<F>(&self, id: usize, f: F) -> Option<&[Piece]>
+    where
+        F: Fn(&[Piece]) -> bool,
+    {
+        let (_, pieces) = self.routes.get(id)?;
+        if f(pieces) {
+            return Some(pieces);
+        }
+        Some(
+            self.variants
+                .iter()
+                .filter(|(i, _)| *i == id)
+                .map(|(_, pieces)| &pieces[..])
+                .find(|pieces| f(pieces))
+                .unwrap_or(pieces),
+        )
+    }
+
+    /// Returns the route id and the pattern of the alias by index.
+    #[must_use]
+    pub fn get_alias(&self, index: usize) -> Option<(usize, &[Piece])> {
+        self.aliases
+            .get(index)
+            .map(|(id, pieces)| (*id, &pieces[..]))
+    }
+}
+
+/// Resolves the value of a node to the route id and the index of the alias, if tagged.
+#[inline]
+pub(crate) fn resolve<'a>(
+    aliases: &'a [(usize, Vec<Piece>)],
+    variants: &'a [(usize, Vec<Piece>)],
+    slot: &'a usize,
+) -> (&'a usize, Option<usize>) {
+    if slot & (ALIAS | VARIANT) == 0 {
+        (slot, None)
+    } else if slot & ALIAS == 0 {
+        (&variants[slot & !VARIANT].0, None)
+    } else {
+        let index = slot & !ALIAS;
+        (&aliases[index].0, Some(index))
+    }
+}
+
+/// Returns the matched pattern by the value of the node, of the alias or the variant if
+/// tagged, else of the route.
+#[inline]
+pub(crate) fn pieces<'a>(
+    aliases: &'a [(usize, Vec<Piece>)],
+    variants: &'a [(usize, Vec<Piece>)],
+    slot: usize,
+    route: &'a [Piece],
+) -> &'a [Piece] {
+    if slot & (ALIAS | VARIANT) == 0 {
+        route
+    } else if slot & ALIAS == 0 {
+        &variants[slot & !VARIANT].1
+    } else {
+        &aliases[slot & !ALIAS].1
+    }
+}
+
+/// Returns the node values with the patterns, of the routes then of the aliases and the
+/// variants.
+pub(crate) fn patterns<'a, T>(
+    routes: &'a [(T, Vec<Piece>)],
+    aliases: &'a [(usize, Vec<Piece>)],
+    variants: &'a [(usize, Vec<Piece>)],
+) -> impl Iterator<Item = (usize, &'a [Piece])> {
+    let tagged = |tag| {
+        move |(index, (_, pieces)): (usize, &'a (usize, Vec<Piece>))| (tag | index, &pieces[..])
+    };
+    routes
+        .iter()
+        .enumerate()
+        .map(|(id, (_, pieces))| (id, &pieces[..]))
+        .chain(aliases.iter().enumerate().map(tagged(ALIAS)))
+        .chain(variants.iter().enumerate().map(tagged(VARIANT)))
+}
@@ -0,0 +1,189 @@
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use smallvec::SmallVec;
+
+use crate::{
+    alias::resolve,
+    find_id,
+    statics::StaticIndex,
+    walk::{Descent, Walk},
+    FrozenPathTree, PathTree, Piece, Prefilter,
+};
+
+/// The matches of a batch of paths, see [`PathTree::find_many`].
+///
+/// The captures of all paths are stored in one flat buffer.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Matches {
+    ids: Vec<Option<usize>>,
+    /// For each path, the range of its captures in `captures`
+    spans: Vec<Range<usize>>,
+    captures: Vec<Range<usize>>,
+}
+
+impl Matches {
+    /// Returns the number of paths.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    /// Returns `true` if there are no paths.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    /// Returns the route id of the path at the index.
+    #[must_use]
+    pub fn id(&self, index: usize) -> Option<usize> {
+        self.ids.get(index).copied().flatten()
+    }
+
+    /// Returns the byte ranges of the parameters of the path at the index, in order.
+    #[must_use]
+    pub fn captures(&self, index: usize) -> &[Range<usize>] {
+        self.spans
+            .get(index)
+            .map_or(&[], |span| &self.captures[span.clone()])
+    }
+
+    /// Returns the route ids and the captures, in the order of the paths.
+    pub fn iter(&self) -> impl Iterator<Item = Option<(usize, &[Range<usize>])>> {
+        self.ids
+            .iter()
+            .zip(self.spans.iter())
+            .map(|(id, span)| id.map(|id| (id, &self.captures[span.clone()])))
+    }
+}
+
+impl<T> PathTree<T> {
+    /// Finds a batch of paths, returning the route ids and the captures.
+    ///
+    /// The capture buffer is shared by the whole batch. A path walks the nodes from the end of
+    /// the static prefix it shares with the previous one, and a path equal to the previous one
+    /// reuses its match, so sorting the input (e.g. log lines) groups the common prefixes.
+    #[must_use]
+    pub fn find_many<'p, I>(&self, paths: I) -> Matches
+    where
+        I: IntoIterator<Item = &'p str>,
+    {
+        batch(
+            Descent::new(&self.node),
+            &self.statics,
+            self.prefilter.as_ref(),
+            &self.aliases,
+            &self.variants,
+            paths,
+            true,
+        )
+    }
+
+    /// Classifies a batch of paths by route id, like [`PathTree::find_many`] without captures.
+    #[must_use]
+    pub fn classify<'p, I>(&self, paths: I) -> Vec<Option<usize>>
+    where
+        I: IntoIterator<Item = &'p str>,
+    {
+        batch(
+            Descent::new(&self.node),
+            &self.statics,
+            self.prefilter.as_ref(),
+            &self.aliases,
+            &self.variants,
+            paths,
+            false,
+        )
+        .ids
+    }
+}
+
+impl<T> FrozenPathTree<T> {
+    /// Finds a batch of paths, see [`PathTree::find_many`].
+    #[must_use]
+    pub fn find_many<'p, I>(&self, paths: I) -> Matches
+    where
+        I: IntoIterator<Item = &'p str>,
+    {
+        batch(
+            Descent::new(self.root()),
+            &self.statics,
+            self.prefilter.as_ref(),
+            &self.aliases,
+            &self.variants,
+            paths,
+            true,
+        )
+    }
+
+    /// Classifies a batch of paths by route id, see [`PathTree::classify`].
+    #[must_use]
+    pub fn classify<'p, I>(&self, paths: I) -> Vec<Option<usize>>
+    where
+        I: IntoIterator<Item = &'p str>,
+    {
+        batch(
+            Descent::new(self.root()),
+            &self.statics,
+            self.prefilter.as_ref(),
+            &self.aliases,
+            &self.variants,
+            paths,
+            false,
+        )
+        .ids
+    }
+}
+
+fn batch<'a, 'p, N, I>(
+    mut descent: Descent<'p, N>,
+    statics: &'a StaticIndex,
+    prefilter: Option<&Prefilter>,
+    aliases: &[(usize, Vec<Piece>)],
+    variants: &[(usize, Vec<Piece>)],
+    paths: I,
+    captures: bool,
+) -> Matches
+where
+    N: Walk<'a, Value = usize>,
+    I: IntoIterator<Item = &'p str>,
+{
+    let paths = paths.into_iter();
+    let mut matches = Matches {
+        ids: Vec::with_capacity(paths.size_hint().0),
+        ..Matches::default()
+    };
+    let mut ranges = SmallVec::new_const();
+    let mut previous: Option<&str> = None;
+
+    for path in paths {
+        if previous == Some(path) {
+            let id = matches.ids[matches.ids.len() - 1];
+            matches.ids.push(id);
+            if captures {
+                let span = matches.spans[matches.spans.len() - 1].clone();
+                matches.spans.push(span);
+            }
+            continue;
+        }
+        previous = Some(path);
+
+        let bytes = path.as_bytes();
+        let id = find_id(statics, prefilter, bytes, &mut ranges, |_, ranges| {
+            descent.find(bytes, ranges)
+        })
+        .map(|slot| *resolve(aliases, variants, slot).0);
+        matches.ids.push(id);
+        if captures {
+            let start = matches.captures.len();
+            if id.is_some() {
+                // the ranges are pushed from the last one
+                matches.captures.extend(ranges.drain(..).rev());
+            }
+            matches.spans.push(start..matches.captures.len());
+        }
+    }
+
+    matches
+}
@@ -0,0 +1,236 @@
+use alloc::{borrow::Cow, collections::BTreeMap, vec::Vec};
+use core::cmp::Ordering;
+
+use crate::{
+    alias,
+    node::{compare, compare_parameters, parameter_key},
+    parser, Key, Node, Parser, PathTree, Piece, StaticIndex,
+};
+
+impl<T> PathTree<T> {
+    /// Builds a tree from the path-values in one pass.
+    ///
+    /// It builds the same tree as inserting them in order, but creates each node once instead
+    /// of splitting the nodes on the way. Sorted patterns are grouped in linear time.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if a pattern expands to more than 256 patterns, see [`PathTree::insert`].
+    #[must_use]
+    pub fn from_sorted<'p, I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = (&'p str, T)>,
+    {
+        let (paths, values): (Vec<_>, Vec<_>) = iter.into_iter().unzip();
+        let pieces = paths
+            .iter()
+            .map(|path| Parser::new(path).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let joined = pieces.iter().map(|pieces| join(pieces)).collect::<Vec<_>>();
+
+        // the root takes the key of the first route, so it is only built when all routes
+        // start with the same byte, and the braces are expanded by inserting
+        let mut firsts =
+            joined
+                .iter()
+                .filter_map(|pieces| pieces.first())
+                .map(|piece| match piece {
+                    Piece::String(s) => Some(s[0]),
+                    Piece::Parameter(..) | Piece::Alternation(..) => None,
+                });
+        let regular = firsts
+            .next()
+            .is_none_or(|first| first.is_some() && firsts.all(|b| b == first))
+            && !paths.iter().any(|path| parser::has_groups(path, false));
+        if !regular {
+            return paths
+                .into_iter()
+                .zip(values)
+                .fold(Self::new(), |mut tree, (path, value)| {
+                    let _ = tree.insert(path, value);
+                    tree
+                });
+        }
+
+        let mut items = joined
+            .iter()
+            .enumerate()
+            .map(|(pos, pieces)| Item {
+                pos,
+                pieces,
+                offset: 0,
+            })
+            .collect::<Vec<_>>();
+
+        let key = items
+            .iter()
+            .filter(|item| !item.pieces.is_empty())
+            .map(Item::bytes)
+            .reduce(|key, bytes| &key[..common(key, bytes)])
+            .unwrap_or_default();
+        for item in items.iter_mut().filter(|item| !item.pieces.is_empty()) {
+            item.advance(key.len());
+        }
+
+        // the route of the same shape keeps the first id and takes the last value
+        let mut source = alloc::vec![usize::MAX; items.len()];
+        let mut node = build(Key::String(key.to_vec()), &mut items, &mut source);
+
+        let mut ids = alloc::vec![0; source.len()];
+        let mut values = values.into_iter().map(Some).collect::<Vec<_>>();
+        let mut pieces = pieces.into_iter().map(Some).collect::<Vec<_>>();
+        let mut routes = Vec::new();
+        for (pos, last) in source.into_iter().enumerate() {
+            if last != usize::MAX {
+                ids[pos] = routes.len();
+                routes.push((values[last].take().unwrap(), pieces[last].take().unwrap()));
+            }
+        }
+        node.remap(&|id| ids[id]);
+
+        let statics = StaticIndex::new(&node, alias::patterns(&routes, &[], &[]));
+
+        Self {
+            id: routes.len(),
+            routes,
+            statics,
+            prefilter: None,
+            aliases: Vec::new(),
+            variants: Vec::new(),
+            names: BTreeMap::new(),
+            optional_groups: false,
+            node,
+        }
+    }
+}
+
+impl<'p, T> FromIterator<(&'p str, T)> for PathTree<T> {
+    fn from_iter<I: IntoIterator<Item = (&'p str, T)>>(iter: I) -> Self {
+        Self::from_sorted(iter)
+    }
+}
+
+impl<'p, T> Extend<(&'p str, T)> for PathTree<T> {
+    fn extend<I: IntoIterator<Item = (&'p str, T)>>(&mut self, iter: I) {
+        for (path, value) in iter {
+            let _ = self.insert(path, value);
+        }
+    }
+}
+
+/// A route being built, the rest of its pieces from the byte offset into the first one.
+struct Item<'a> {
+    pos: usize,
+    pieces: &'a [Piece],
+    offset: usize,
+}
+
+impl<'a> Item<'a> {
+    fn bytes(&self) -> &'a [u8] {
+        match &self.pieces[0] {
+            Piece::String(s) => &s[self.offset..],
+            Piece::Parameter(..) | Piece::Alternation(..) => &[],
+        }
+    }
+
+    fn advance(&mut self, n: usize) {
+        self.offset += n;
+        if self.bytes().is_empty() {
+            self.pieces = &self.pieces[1..];
+            self.offset = 0;
+        }
+    }
+
+    /// Orders like the nodes: the ended routes, the strings by the first byte, the parameters.
+    fn order(&self, other: &Self) -> Ordering {
+        match (self.pieces.first(), other.pieces.first()) {
+            (None, None) => Ordering::Equal,
+            (Some(Piece::String(_)), Some(Piece::String(_))) => {
+                compare(self.bytes()[0], other.bytes()[0])
+            }
+            (None | Some(Piece::String(_)), Some(_)) => Ordering::Less,
+            (Some(_), None | Some(Piece::String(_))) => Ordering::Greater,
+            (Some(Piece::Parameter(_, a)), Some(Piece::Parameter(_, b))) => a.cmp(b),
+            (Some(a), Some(b)) => compare_parameters(&parameter_key(a), &parameter_key(b)),
+        }
+    }
+}
+
+/// Builds the node of the items which have passed its key.
+fn build(key: Key, items: &mut [Item<'_>], source: &mut [usize]) -> Node<usize> {
+    let mut node = Node::new(key, None);
+
+    items.sort_by(Item::order);
+
+    let ended = items
+        .iter()
+        .take_while(|item| item.pieces.is_empty())
+        .count();
+    if ended > 0 {
+        let first = items[..ended].iter().map(|item| item.pos).min().unwrap();
+        let last = items[..ended].iter().map(|item| item.pos).max().unwrap();
+        node.value = Some(first);
+        source[first] = last;
+    }
+
+    let mut rest = &mut items[ended..];
+    while !rest.is_empty() {
+        let n = rest
+            .iter()
+            .take_while(|item| rest[0].order(item) == Ordering::Equal)
+            .count();
+        let (run, tail) = rest.split_at_mut(n);
+        rest = tail;
+
+        match &run[0].pieces[0] {
+            Piece::String(_) => {
+                let bytes = run[0].bytes();
+                let n = run[1..]
+                    .iter()
+                    .fold(bytes.len(), |n, item| common(&bytes[..n], item.bytes()));
+                for item in run.iter_mut() {
+                    item.advance(n);
+                }
+                node.nodes0.get_or_insert_with(Vec::new).push(build(
+                    Key::String(bytes[..n].to_vec()),
+                    run,
+                    source,
+                ));
+            }
+            piece => {
+                let key = parameter_key(piece);
+                for item in run.iter_mut() {
+                    item.pieces = &item.pieces[1..];
+                }
+                node.nodes1
+                    .get_or_insert_with(Vec::new)
+                    .push(build(key, run, source));
+            }
+        }
+    }
+
+    node
+}
+
+/// Joins the consecutive strings (escapes), as they are inserted.
+fn join(pieces: &[Piece]) -> Cow<'_, [Piece]> {
+    if pieces
+        .windows(2)
+        .all(|w| !matches!(w, [Piece::String(_), Piece::String(_)]))
+    {
+        return Cow::Borrowed(pieces);
+    }
+
+    let mut joined = Vec::<Piece>::with_capacity(pieces.len());
+    for piece in pieces {
+        match (piece, joined.last_mut()) {
+            (Piece::String(s), Some(Piece::String(last))) => last.extend_from_slice(s),
+            _ => joined.push(piece.clone()),
+        }
+    }
+    Cow::Owned(joined)
+}
+
+fn common(a: &[u8], b: &[u8]) -> usize {
+    a.iter().zip(b).take_while(|(a, b)| a == b).count()
+}
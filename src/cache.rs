@@ -0,0 +1,245 @@
+use alloc::{boxed::Box, vec::Vec};
+use core::{hash::BuildHasher, ops::Range, str::from_utf8};
+use std::{
+    collections::{hash_map::RandomState, HashMap},
+    sync::{Mutex, MutexGuard, PoisonError},
+};
+
+use smallvec::SmallVec;
+
+use crate::{
+    alias::{self, resolve},
+    Path, PathTree,
+};
+
+/// A [`PathTree`] which memoizes the matches of the recently found paths.
+///
+/// The cache is bounded, shared by concurrent readers and cleared on insert. It pays off when
+/// the traffic is skewed towards a few paths matched by costly routes.
+///
+/// Large caches are split in shards by the hash of the paths, each one a LRU behind its own
+/// mutex, and a lock is never held while walking the tree.
+#[derive(Debug)]
+pub struct CachedPathTree<T> {
+    tree: PathTree<T>,
+    /// Lends the ids to the cached matches
+    ids: Vec<usize>,
+    hasher: RandomState,
+    shards: Box<[Mutex<Lru>]>,
+}
+
+/// The capacity of a shard before the cache is split.
+const SHARD_CAPACITY: usize = 64;
+const MAX_SHARDS: usize = 16;
+
+impl<T> CachedPathTree<T> {
+    /// Creates a new [`CachedPathTree`] which caches up to `capacity` paths.
+    #[must_use]
+    pub fn new(tree: PathTree<T>, capacity: usize) -> Self {
+        let n = (capacity / SHARD_CAPACITY).clamp(1, MAX_SHARDS);
+        Self {
+            ids: (0..tree.routes.len()).collect(),
+            tree,
+            hasher: RandomState::new(),
+            // spreads the remainder over the first shards
+            shards: (0..n)
+                .map(|i| Mutex::new(Lru::new(capacity / n + usize::from(i < capacity % n))))
+                .collect(),
+        }
+    }
+
+    /// Inserts a part path-value to the tree, clears the cache and returns the id.
+    #[must_use]
+    pub fn insert(&mut self, path: &str, value: T) -> usize {
+        for shard in &mut self.shards {
+            shard
+                .get_mut()
+                .unwrap_or_else(PoisonError::into_inner)
+                .clear();
+        }
+        let id = self.tree.insert(path, value);
+        self.ids.extend(self.ids.len()..self.tree.routes.len());
+        id
+    }
+
+    /// Returns the [`Path`] by the given path, from the cache if it was recently found.
+    #[must_use]
+    pub fn find<'a, 'b>(&'a self, path: &'b str) -> Option<(&'a T, Path<'a, 'b>)> {
+        let bytes = path.as_bytes();
+        // the remainder is below the number of shards, so it fits in a `usize`
+        let i = self.hasher.hash_one(path) % self.shards.len() as u64;
+        let shard = &self.shards[usize::try_from(i).unwrap_or_default()];
+        let raws = |ranges: &[Range<usize>]| {
+            ranges
+                .iter()
+                .filter_map(|r| from_utf8(&bytes[r.clone()]).ok())
+                .collect()
+        };
+
+        let hit = lock(shard)
+            .get(path)
+            .map(|(slot, ranges)| (slot, raws(ranges)));
+        let (slot, raws) = if let Some(hit) = hit {
+            hit
+        } else {
+            // the lock is released while walking, a concurrent miss may insert the path too
+            let mut ranges = SmallVec::new_const();
+            let slot = *self.tree.find_id(bytes, &mut ranges)?;
+            ranges.reverse();
+            lock(shard).insert(path, slot, &ranges);
+            (slot, raws(&ranges))
+        };
+
+        let (&id, alias) = resolve(&self.tree.aliases, &self.tree.variants, &slot);
+        self.tree.routes.get(id).map(|(value, pieces)| {
+            (
+                value,
+                Path {
+                    id: &self.ids[id],
+                    pieces: alias::pieces(&self.tree.aliases, &self.tree.variants, slot, pieces),
+                    raws,
+                    query: None,
+                    alias,
+                },
+            )
+        })
+    }
+
+    /// Returns the number of cached paths.
+    #[must_use]
+    pub fn cached(&self) -> usize {
+        self.shards.iter().map(|shard| lock(shard).len()).sum()
+    }
+
+    /// Returns the inner tree.
+    #[must_use]
+    pub fn tree(&self) -> &PathTree<T> {
+        &self.tree
+    }
+
+    /// Consumes the cache and returns the inner tree.
+    #[must_use]
+    pub fn into_inner(self) -> PathTree<T> {
+        self.tree
+    }
+}
+
+fn lock(shard: &Mutex<Lru>) -> MutexGuard<'_, Lru> {
+    shard.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+const NIL: usize = usize::MAX;
+
+#[derive(Debug)]
+struct Entry {
+    path: Box<str>,
+    id: usize,
+    ranges: Box<[Range<usize>]>,
+    prev: usize,
+    next: usize,
+}
+
+/// A least recently used map, its entries are linked by indexes from the most recent one.
+#[derive(Debug)]
+struct Lru {
+    capacity: usize,
+    map: HashMap<Box<str>, usize>,
+    entries: Vec<Entry>,
+    head: usize,
+    tail: usize,
+}
+
+impl Lru {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            map: HashMap::with_capacity(capacity),
+            entries: Vec::with_capacity(capacity),
+            head: NIL,
+            tail: NIL,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    fn clear(&mut self) {
+        self.map.clear();
+        self.entries.clear();
+        self.head = NIL;
+        self.tail = NIL;
+    }
+
+    fn get(&mut self, path: &str) -> Option<(usize, &[Range<usize>])> {
+        let i = *self.map.get(path)?;
+        self.detach(i);
+        self.attach(i);
+        let entry = &self.entries[i];
+        Some((entry.id, &entry.ranges))
+    }
+
+    fn insert(&mut self, path: &str, id: usize, ranges: &[Range<usize>]) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        if let Some(&i) = self.map.get(path) {
+            let entry = &mut self.entries[i];
+            entry.id = id;
+            entry.ranges = ranges.into();
+            self.detach(i);
+            self.attach(i);
+            return;
+        }
+
+        let i = if self.entries.len() < self.capacity {
+            self.entries.push(Entry {
+                path: path.into(),
+                id,
+                ranges: ranges.into(),
+                prev: NIL,
+                next: NIL,
+            });
+            self.entries.len() - 1
+        } else {
+            // evicts the least recent one
+            let i = self.tail;
+            self.detach(i);
+            let entry = &mut self.entries[i];
+            self.map.remove(&entry.path);
+            entry.path = path.into();
+            entry.id = id;
+            entry.ranges = ranges.into();
+            i
+        };
+
+        self.map.insert(path.into(), i);
+        self.attach(i);
+    }
+
+    fn detach(&mut self, i: usize) {
+        let (prev, next) = (self.entries[i].prev, self.entries[i].next);
+        if prev == NIL {
+            self.head = next;
+        } else {
+            self.entries[prev].next = next;
+        }
+        if next == NIL {
+            self.tail = prev;
+        } else {
+            self.entries[next].prev = prev;
+        }
+    }
+
+    fn attach(&mut self, i: usize) {
+        self.entries[i].prev = NIL;
+        self.entries[i].next = self.head;
+        if self.head == NIL {
+            self.tail = i;
+        } else {
+            self.entries[self.head].prev = i;
+        }
+        self.head = i;
+    }
+}
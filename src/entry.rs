@@ -0,0 +1,145 @@
+use alloc::vec::Vec;
+
+use crate::{alias::resolve, node::RadixNode, PathTree, Piece};
+
+impl<T> PathTree<T> {
+    /// Gets the entry of the pattern for in-place manipulation.
+    ///
+    /// Patterns of the same shape share an entry, e.g. `/users/:id` and `/users/:name`, and
+    /// the pattern of an alias is the entry of its route. The groups are expanded like
+    /// [`PathTree::insert`], the entry is of the first pattern.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the pattern expands to more than 256 patterns.
+    #[must_use]
+    pub fn entry(&mut self, path: &str) -> Entry<'_, T> {
+        let patterns = self.expand(path);
+        match self
+            .node
+            .get(&patterns[0])
+            .map(|slot| *resolve(&self.aliases, &self.variants, slot).0)
+        {
+            Some(id) => Entry::Occupied(OccupiedEntry { tree: self, id }),
+            None => Entry::Vacant(VacantEntry {
+                tree: self,
+                patterns,
+            }),
+        }
+    }
+}
+
+/// An entry of a pattern, see [`PathTree::entry`].
+#[derive(Debug)]
+pub enum Entry<'a, T> {
+    /// The pattern is inserted.
+    Occupied(OccupiedEntry<'a, T>),
+    /// The pattern is not inserted.
+    Vacant(VacantEntry<'a, T>),
+}
+
+impl<'a, T> Entry<'a, T> {
+    /// Inserts the value if vacant, and returns the value of the route.
+    pub fn or_insert(self, value: T) -> &'a mut T {
+        self.or_insert_with(|| value)
+    }
+
+    /// Inserts the result of `f` if vacant, and returns the value of the route.
+    pub fn or_insert_with<F: FnOnce() -> T>(self, f: F) -> &'a mut T {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(f()),
+        }
+    }
+
+    /// Inserts the default value if vacant, and returns the value of the route.
+    pub fn or_default(self) -> &'a mut T
+    where
+        T: Default,
+    {
+        self.or_insert_with(T::default)
+    }
+
+    /// Modifies the value if occupied.
+    #[must_use]
+    pub fn and_modify<F: FnOnce(&mut T)>(mut self, f: F) -> Self {
+        if let Entry::Occupied(entry) = &mut self {
+            f(entry.get_mut());
+        }
+        self
+    }
+
+    /// Returns the id of the route if occupied.
+    #[must_use]
+    pub fn id(&self) -> Option<usize> {
+        match self {
+            Entry::Occupied(entry) => Some(entry.id),
+            Entry::Vacant(_) => None,
+        }
+    }
+}
+
+/// An inserted pattern, see [`Entry`].
+#[derive(Debug)]
+pub struct OccupiedEntry<'a, T> {
+    tree: &'a mut PathTree<T>,
+    id: usize,
+}
+
+impl<'a, T> OccupiedEntry<'a, T> {
+    /// Returns the id of the route.
+    #[must_use]
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    /// Returns the pieces of the route, as they were inserted.
+    #[must_use]
+    pub fn pieces(&self) -> &[Piece] {
+        &self.tree.routes[self.id].1
+    }
+
+    /// Returns the value of the route.
+    #[must_use]
+    pub fn get(&self) -> &T {
+        &self.tree.routes[self.id].0
+    }
+
+    /// Returns the value of the route mutably.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.tree.routes[self.id].0
+    }
+
+    /// Converts into the value of the route, bound to the tree.
+    #[must_use]
+    pub fn into_mut(self) -> &'a mut T {
+        &mut self.tree.routes[self.id].0
+    }
+
+    /// Replaces the value and returns the old one, the pieces are kept.
+    pub fn insert(&mut self, value: T) -> T {
+        core::mem::replace(self.get_mut(), value)
+    }
+}
+
+/// A pattern not inserted yet, see [`Entry`].
+#[derive(Debug)]
+pub struct VacantEntry<'a, T> {
+    tree: &'a mut PathTree<T>,
+    /// The parsed patterns expanded from the groups, the first one is the route
+    patterns: Vec<Vec<Piece>>,
+}
+
+impl<'a, T> VacantEntry<'a, T> {
+    /// Returns the parsed pieces of the pattern, the first one of its groups.
+    #[must_use]
+    pub fn pieces(&self) -> &[Piece] {
+        &self.patterns[0]
+    }
+
+    /// Inserts the value and returns it, see [`PathTree::insert`].
+    pub fn insert(self, value: T) -> &'a mut T {
+        let id = self.tree.insert_expanded(self.patterns, value);
+        &mut self.tree.routes[id].0
+    }
+}
@@ -0,0 +1,482 @@
+use alloc::{
+    collections::{BTreeMap, VecDeque},
+    vec::Vec,
+};
+use core::{ops::Range, slice::Iter, str::from_utf8};
+
+use smallvec::SmallVec;
+
+use crate::{
+    alias::{self, resolve},
+    find_id,
+    node::compare,
+    statics::StaticIndex,
+    url::split_target,
+    walk::{self, KeyRef, Walk},
+    Captures, Key, Kind, Node, Path, Piece, Prefilter,
+};
+
+/// Nodes with at least this many string children get a first-byte jump table.
+const JUMP_TABLE_THRESHOLD: usize = 8;
+
+/// A read-only path tree with a flat node layout.
+///
+/// Built by [`PathTree::freeze`](crate::PathTree::freeze), it matches exactly like the tree
+/// it was frozen from, but keeps all nodes in one contiguous array: children are index ranges
+/// instead of nested vectors and the string keys are interned into a single byte buffer.
+/// High-fanout nodes also get a first-byte jump table instead of a binary search.
+#[derive(Clone, Debug)]
+pub struct FrozenPathTree<T> {
+    routes: Vec<(T, Vec<Piece>)>,
+    pub(crate) aliases: Vec<(usize, Vec<Piece>)>,
+    pub(crate) variants: Vec<(usize, Vec<Piece>)>,
+    pub(crate) statics: StaticIndex,
+    pub(crate) prefilter: Option<Prefilter>,
+    nodes: FrozenNodes,
+}
+
+impl<T> FrozenPathTree<T> {
+    pub(crate) fn new(
+        node: &Node<usize>,
+        routes: Vec<(T, Vec<Piece>)>,
+        aliases: Vec<(usize, Vec<Piece>)>,
+        variants: Vec<(usize, Vec<Piece>)>,
+        statics: StaticIndex,
+        prefilter: Option<Prefilter>,
+    ) -> Self {
+        Self {
+            routes,
+            aliases,
+            variants,
+            statics,
+            prefilter,
+            nodes: FrozenNodes::new(node),
+        }
+    }
+
+    /// Returns the [`Path`] by the given path.
+    #[must_use]
+    pub fn find<'a, 'b>(&'a self, path: &'b str) -> Option<(&'a T, Path<'a, 'b>)> {
+        let bytes = path.as_bytes();
+        let mut ranges = SmallVec::new_const();
+        self.find_id(bytes, &mut ranges).and_then(|slot| {
+            let (id, alias) = resolve(&self.aliases, &self.variants, slot);
+            self.routes.get(*id).map(|(value, pieces)| {
+                (
+                    value,
+                    Path {
+                        id,
+                        pieces: alias::pieces(&self.aliases, &self.variants, *slot, pieces),
+                        raws: ranges
+                            .into_iter()
+                            .filter_map(|r| from_utf8(&bytes[r]).ok())
+                            .rev()
+                            .collect(),
+                        query: None,
+                        alias,
+                    },
+                )
+            })
+        })
+    }
+
+    /// Returns the [`Path`] by the request target, see [`PathTree::find_target`].
+    ///
+    /// [`PathTree::find_target`]: crate::PathTree::find_target
+    #[must_use]
+    pub fn find_target<'a, 'b>(&'a self, target: &'b str) -> Option<(&'a T, Path<'a, 'b>)> {
+        let (path, query) = split_target(target);
+        self.find(path)
+            .map(|(value, path)| (value, Path { query, ..path }))
+    }
+
+    /// Finds the path and stores the parameters into the reusable [`Captures`].
+    pub fn find_into<'a>(
+        &'a self,
+        path: &str,
+        captures: &mut Captures,
+    ) -> Option<(&'a T, &'a [Piece])> {
+        let slot = self.find_id(path.as_bytes(), &mut captures.ranges)?;
+        let (&id, _) = resolve(&self.aliases, &self.variants, slot);
+        captures.finish(id);
+        self.routes.get(id).map(|(value, pieces)| {
+            (
+                value,
+                alias::pieces(&self.aliases, &self.variants, *slot, pieces),
+            )
+        })
+    }
+
+    /// Finds the path and calls `f` with each parameter name and value, in order.
+    pub fn find_with<F>(&self, path: &str, mut f: F) -> Option<&T>
+    where
+        F: FnMut(&str, &str),
+    {
+        let mut captures = Captures::new();
+        let (value, pieces) = self.find_into(path, &mut captures)?;
+        captures.params(pieces, path).for_each(|(k, v)| f(k, v));
+        Some(value)
+    }
+
+    #[inline]
+    pub(crate) fn find_id(
+        &self,
+        bytes: &[u8],
+        ranges: &mut SmallVec<[Range<usize>; 8]>,
+    ) -> Option<&usize> {
+        find_id(
+            &self.statics,
+            self.prefilter.as_ref(),
+            bytes,
+            ranges,
+            |bytes, ranges| walk::find_with(self.root(), 0, bytes, ranges),
+        )
+    }
+
+    /// Drops the first-byte jump tables to save their memory, 512 bytes each, the string nodes
+    /// are binary searched instead.
+    #[must_use]
+    pub fn without_jump_tables(mut self) -> Self {
+        self.nodes.tables = Vec::new();
+        for node in &mut self.nodes.nodes {
+            node.jump = None;
+        }
+        self
+    }
+
+    /// Returns the root node for the walk.
+    #[inline]
+    pub(crate) fn root(&self) -> FrozenRef<'_> {
+        FrozenRef {
+            nodes: &self.nodes,
+            node: &self.nodes.nodes[0],
+        }
+    }
+
+    /// Returns the [`Prefilter`] if it was enabled on the tree.
+    #[must_use]
+    pub fn prefilter(&self) -> Option<&Prefilter> {
+        self.prefilter.as_ref()
+    }
+
+    /// Gets the route by id.
+    #[must_use]
+    #[inline]
+    pub fn get_route(&self, index: usize) -> Option<&(T, Vec<Piece>)> {
+        self.routes.get(index)
+    }
+
+    pub fn iter(&self) -> Iter<'_, (T, Vec<Piece>)> {
+        self.routes.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a FrozenPathTree<T> {
+    type Item = &'a (T, Vec<Piece>);
+    type IntoIter = Iter<'a, (T, Vec<Piece>)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum FrozenKey {
+    /// Range of the key in the interned bytes.
+    String(u32, u32),
+    Parameter(Kind),
+    /// Range of the literals in the literal ranges.
+    Alternation(u32, u32),
+}
+
+#[derive(Clone, Debug)]
+struct FrozenNode {
+    key: FrozenKey,
+    value: Option<usize>,
+    /// Stores the range of string nodes
+    nodes0: (u32, u32),
+    /// Stores the range of parameter nodes
+    nodes1: (u32, u32),
+    /// Index of the first-byte jump table of string nodes
+    jump: Option<u32>,
+    /// Minimum length of the bytes matched by this node and a route below it
+    min: usize,
+}
+
+/// The flattened nodes, laid out in breadth-first order so that the children of
+/// a node are always adjacent.
+#[derive(Clone, Debug)]
+struct FrozenNodes {
+    nodes: Vec<FrozenNode>,
+    bytes: Vec<u8>,
+    /// Ranges of the alternation literals in the interned bytes
+    literals: Vec<(u32, u32)>,
+    /// Maps a first byte to the offset + 1 of a string node, `0` means no node.
+    tables: Vec<[u16; 256]>,
+}
+
+impl FrozenNodes {
+    fn new(root: &Node<usize>) -> Self {
+        let mut nodes = Vec::new();
+        let mut bytes = Vec::new();
+        let mut literals = Vec::new();
+        let mut tables = Vec::new();
+        let mut interned = BTreeMap::<&[u8], (u32, u32)>::new();
+        let mut queue = VecDeque::new();
+
+        Self::push(root, &mut nodes, &mut bytes, &mut literals, &mut interned);
+        queue.push_back((root, 0));
+
+        while let Some((node, index)) = queue.pop_front() {
+            let start = to_u32(nodes.len());
+            for child in node.nodes0.iter().flatten() {
+                queue.push_back((child, nodes.len()));
+                Self::push(child, &mut nodes, &mut bytes, &mut literals, &mut interned);
+            }
+            let middle = to_u32(nodes.len());
+            for child in node.nodes1.iter().flatten() {
+                queue.push_back((child, nodes.len()));
+                Self::push(child, &mut nodes, &mut bytes, &mut literals, &mut interned);
+            }
+            let end = to_u32(nodes.len());
+            nodes[index].nodes0 = (start, middle);
+            nodes[index].nodes1 = (middle, end);
+
+            if (middle - start) as usize >= JUMP_TABLE_THRESHOLD {
+                let mut table = [0; 256];
+                for (offset, child) in nodes[start as usize..middle as usize].iter().enumerate() {
+                    if let FrozenKey::String(key_start, _) = child.key {
+                        table[bytes[key_start as usize] as usize] =
+                            u16::try_from(offset + 1).expect("at most 256 string nodes");
+                    }
+                }
+                nodes[index].jump = Some(to_u32(tables.len()));
+                tables.push(table);
+            }
+        }
+
+        let mut nodes = Self {
+            nodes,
+            bytes,
+            literals,
+            tables,
+        };
+        // the children are laid out after their parent, so their lengths are known first
+        for index in (0..nodes.nodes.len()).rev() {
+            nodes.nodes[index].min = nodes.min_length(&nodes.nodes[index]);
+        }
+        nodes
+    }
+
+    /// Computes the minimum length from the key and the children.
+    fn min_length(&self, node: &FrozenNode) -> usize {
+        let children = |(start, end): (u32, u32)| self.nodes[start as usize..end as usize].iter();
+        let min = children(node.nodes0)
+            .chain(children(node.nodes1))
+            .map(|node| node.min)
+            .fold(
+                if node.value.is_some() { 0 } else { usize::MAX },
+                usize::min,
+            );
+
+        match node.key {
+            FrozenKey::String(start, len) => {
+                let n = len as usize;
+                let min = min.saturating_add(n);
+
+                // the last `/` can be skipped by the segment parameters
+                if self.key(start, len).last() == Some(&b'/') {
+                    children(node.nodes1)
+                        .filter(|node| {
+                            matches!(node.key,
+                                FrozenKey::Parameter(pk)
+                                    if pk == Kind::OptionalSegment
+                                        || pk == Kind::ZeroOrMoreSegment
+                            )
+                        })
+                        .fold(min, |min, node| min.min(node.min.saturating_add(n - 1)))
+                } else {
+                    min
+                }
+            }
+            // a parameter followed by a string may still be empty
+            FrozenKey::Parameter(_) => min,
+            FrozenKey::Alternation(first, len) => {
+                let shortest = self.literals[first as usize..(first + len) as usize]
+                    .iter()
+                    .map(|&(_, len)| len as usize)
+                    .min()
+                    .unwrap_or_default();
+                min.saturating_add(shortest)
+            }
+        }
+    }
+
+    fn push<'n>(
+        node: &'n Node<usize>,
+        nodes: &mut Vec<FrozenNode>,
+        bytes: &mut Vec<u8>,
+        literals: &mut Vec<(u32, u32)>,
+        interned: &mut BTreeMap<&'n [u8], (u32, u32)>,
+    ) {
+        let mut intern = |s: &'n [u8]| {
+            *interned.entry(s).or_insert_with(|| {
+                let start = bytes.len();
+                bytes.extend_from_slice(s);
+                (to_u32(start), to_u32(s.len()))
+            })
+        };
+        let key = match &node.key {
+            Key::String(s) => {
+                let (start, len) = intern(s);
+                FrozenKey::String(start, len)
+            }
+            Key::Parameter(k) => FrozenKey::Parameter(*k),
+            Key::Alternation(list) => {
+                let start = literals.len();
+                literals.extend(list.iter().map(|literal| intern(literal)));
+                FrozenKey::Alternation(to_u32(start), to_u32(list.len()))
+            }
+        };
+        nodes.push(FrozenNode {
+            key,
+            value: node.value,
+            nodes0: (0, 0),
+            nodes1: (0, 0),
+            jump: None,
+            min: 0,
+        });
+    }
+
+    #[inline]
+    fn key(&self, start: u32, len: u32) -> &[u8] {
+        &self.bytes[start as usize..(start + len) as usize]
+    }
+}
+
+/// A node of the flat layout borrowed for the walk.
+#[derive(Clone, Copy)]
+pub(crate) struct FrozenRef<'a> {
+    nodes: &'a FrozenNodes,
+    node: &'a FrozenNode,
+}
+
+impl<'a> FrozenRef<'a> {
+    #[inline]
+    fn children(self, (start, end): (u32, u32)) -> Option<FrozenIter<'a>> {
+        (start < end).then(|| FrozenIter {
+            nodes: self.nodes,
+            iter: self.nodes.nodes[start as usize..end as usize].iter(),
+        })
+    }
+}
+
+impl<'a> Walk<'a> for FrozenRef<'a> {
+    type Value = usize;
+    type Nodes = FrozenIter<'a>;
+
+    #[inline]
+    fn key(self) -> KeyRef<'a> {
+        match self.node.key {
+            FrozenKey::String(start, len) => KeyRef::String(self.nodes.key(start, len)),
+            FrozenKey::Parameter(k) => KeyRef::Parameter(k),
+            FrozenKey::Alternation(..) => KeyRef::Alternation,
+        }
+    }
+
+    #[inline]
+    fn literal(self, index: usize) -> Option<&'a [u8]> {
+        match self.node.key {
+            FrozenKey::Alternation(first, len) if index < len as usize => {
+                let (start, len) = self.nodes.literals[first as usize + index];
+                Some(self.nodes.key(start, len))
+            }
+            _ => None,
+        }
+    }
+
+    #[inline]
+    fn value(self) -> Option<&'a usize> {
+        self.node.value.as_ref()
+    }
+
+    #[inline]
+    fn nodes0(self) -> Option<FrozenIter<'a>> {
+        self.children(self.node.nodes0)
+    }
+
+    #[inline]
+    fn nodes1(self) -> Option<FrozenIter<'a>> {
+        self.children(self.node.nodes1)
+    }
+
+    /// Finds the string node starting with the first byte, via the jump table if there is one.
+    #[inline]
+    fn child(self, bytes: &[u8]) -> Option<Self> {
+        let (start, end) = self.node.nodes0;
+        let nodes = &self.nodes.nodes[start as usize..end as usize];
+        let i = match self.node.jump {
+            Some(i) => (self.nodes.tables[i as usize][bytes[0] as usize] as usize).checked_sub(1),
+            None => nodes
+                .binary_search_by(|node| {
+                    let node = FrozenRef {
+                        nodes: self.nodes,
+                        node,
+                    };
+                    compare(node.first_byte(), bytes[0])
+                })
+                .ok(),
+        };
+        i.map(|i| FrozenRef {
+            nodes: self.nodes,
+            node: &nodes[i],
+        })
+    }
+
+    #[inline]
+    fn min(self) -> usize {
+        self.node.min
+    }
+
+    #[inline]
+    fn first_byte(self) -> u8 {
+        match self.node.key {
+            FrozenKey::String(start, _) => self.nodes.bytes[start as usize],
+            FrozenKey::Parameter(_) | FrozenKey::Alternation(..) => unreachable!(),
+        }
+    }
+
+    #[inline]
+    fn is_leaf(self) -> bool {
+        self.node.nodes0.0 == self.node.nodes0.1 && self.node.nodes1.0 == self.node.nodes1.1
+    }
+}
+
+/// The children of a node in the flat layout.
+pub(crate) struct FrozenIter<'a> {
+    nodes: &'a FrozenNodes,
+    iter: Iter<'a, FrozenNode>,
+}
+
+impl<'a> Iterator for FrozenIter<'a> {
+    type Item = FrozenRef<'a>;
+
+    #[inline]
+    fn next(&mut self) -> Option<FrozenRef<'a>> {
+        let nodes = self.nodes;
+        self.iter.next().map(|node| FrozenRef { nodes, node })
+    }
+}
+
+impl DoubleEndedIterator for FrozenIter<'_> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let nodes = self.nodes;
+        self.iter.next_back().map(|node| FrozenRef { nodes, node })
+    }
+}
+
+#[inline]
+fn to_u32(n: usize) -> u32 {
+    u32::try_from(n).expect("frozen tree exceeds u32::MAX")
+}
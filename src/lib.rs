@@ -137,25 +137,88 @@
 #![warn(rust_2018_idioms, unreachable_pub)]
 
 extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
 
 use alloc::{
+    collections::BTreeMap,
     string::{String, ToString},
     vec::Vec,
 };
-use core::{slice::Iter, str::from_utf8};
+use core::{ops::Range, slice::Iter, str::from_utf8};
 use smallvec::SmallVec;
 
+mod alias;
+use alias::{resolve, ALIAS, VARIANT};
+
+mod batch;
+pub use batch::Matches;
+
+mod build;
+
+#[cfg(feature = "cache")]
+mod cache;
+#[cfg(feature = "cache")]
+pub use cache::CachedPathTree;
+
+mod entry;
+pub use entry::{Entry, OccupiedEntry, VacantEntry};
+
+mod frozen;
+pub use frozen::FrozenPathTree;
+
 mod node;
+use node::RadixNode;
 pub use node::{Key, Node};
 
 mod parser;
 pub use parser::{Kind, Parser, Piece, Position};
 
+mod persistent;
+pub use persistent::PersistentPathTree;
+
+mod prefilter;
+pub use prefilter::Prefilter;
+
+mod rewrite;
+pub use rewrite::Rewriter;
+
+mod scan;
+
+#[cfg(feature = "std")]
+mod shared;
+#[cfg(feature = "std")]
+pub use shared::{SharedPathTree, Snapshot};
+
+mod statics;
+use statics::{static_bytes, StaticIndex};
+
+mod url;
+pub use url::UrlError;
+
+mod walk;
+
 /// A path tree.
 #[derive(Clone, Debug)]
 pub struct PathTree<T> {
     id: usize,
     routes: Vec<(T, Vec<Piece>)>,
+    statics: StaticIndex,
+    prefilter: Option<Prefilter>,
+    /// The route ids and the patterns of the aliases
+    aliases: Vec<(usize, Vec<Piece>)>,
+    /// The route ids and the patterns expanded from their groups, besides the first one
+    variants: Vec<(usize, Vec<Piece>)>,
+    /// Maps the route names to the ids
+    names: BTreeMap<String, usize>,
+    /// Expands the optional groups of the inserted patterns
+    optional_groups: bool,
+    /// The root node of the patterns.
+    ///
+    /// The values of the nodes of the routes are their ids, the values of the nodes of the
+    /// aliases have the highest bit set, e.g. `usize::MAX / 2 + 1` for the first alias, and
+    /// the ones of the patterns expanded from the groups the second highest bit.
+    /// [`PathTree::find`] resolves them to the ids of their routes.
     pub node: Node<usize>,
 }
 
@@ -172,64 +235,268 @@ impl<T> PathTree<T> {
         Self {
             id: 0,
             routes: Vec::new(),
+            statics: StaticIndex::default(),
+            prefilter: None,
+            aliases: Vec::new(),
+            variants: Vec::new(),
+            names: BTreeMap::new(),
+            optional_groups: false,
             node: Node::new(Key::String(Vec::new()), None),
         }
     }
 
+    /// Enables a [`Prefilter`] which rejects the paths no route can match before the walk.
+    ///
+    /// It pays off when many paths miss, e.g. scanner traffic, and no route starts with a
+    /// parameter.
+    #[must_use]
+    pub fn with_prefilter(mut self) -> Self {
+        self.prefilter = Some(Prefilter::new(
+            alias::patterns(&self.routes, &self.aliases, &self.variants).map(|(_, pieces)| pieces),
+        ));
+        self
+    }
+
+    /// Expands the optional groups `(...)` of the patterns inserted from now on, see
+    /// [`Parser::expand_optional`], e.g. `/archive(/:year(/:month))` matches `/archive` and
+    /// `/archive/2024`.
+    ///
+    /// Without it the parentheses are literal, e.g. `/wiki/Rust_(language)`.
+    #[must_use]
+    pub fn with_optional_groups(mut self) -> Self {
+        self.optional_groups = true;
+        self
+    }
+
+    /// Returns the [`Prefilter`] if enabled.
+    #[must_use]
+    pub fn prefilter(&self) -> Option<&Prefilter> {
+        self.prefilter.as_ref()
+    }
+
     /// Inserts a part path-value to the tree and returns the id.
+    ///
+    /// The brace groups are expanded, see [`Parser::expand`], and the optional groups if
+    /// enabled, see [`PathTree::with_optional_groups`]: the first pattern is the route and the
+    /// others are its variants, e.g. `/v{1,2}/users/:id` matches `/v2/users/1` and generates
+    /// `/v1/users/1`. The variants are not aliases, a match of one has no [`Path::alias`]. The
+    /// patterns already inserted as routes or aliases are left to them.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the pattern expands to more than 256 patterns.
     #[must_use]
     pub fn insert(&mut self, path: &str, value: T) -> usize {
-        let mut node = &mut self.node;
+        if !parser::has_groups(path, self.optional_groups) {
+            return self.insert_parsed(parse(path), value);
+        }
+        self.insert_expanded(self.expand(path), value)
+    }
 
-        let (overwritten, pieces) = if path.is_empty() {
-            (false, Vec::new())
-        } else {
-            let pieces = Parser::new(path).collect::<Vec<_>>();
-            node = pieces.iter().fold(node, |node, piece| match piece {
-                Piece::String(s) => node.insert_bytes(&s[..]),
-                Piece::Parameter(_, k) => node.insert_parameter(*k),
-            });
-            (true, pieces)
+    /// Parses the patterns the groups of the path expand to, the first one is the route.
+    fn expand(&self, path: &str) -> Vec<Vec<Piece>> {
+        parser::expand(path, self.optional_groups)
+            .iter()
+            .map(|pattern| parse(pattern))
+            .collect()
+    }
+
+    /// Inserts the first pattern as the route and the others as its variants.
+    fn insert_expanded(&mut self, patterns: Vec<Vec<Piece>>, value: T) -> usize {
+        let mut patterns = patterns.into_iter();
+        let id = self.insert_parsed(patterns.next().unwrap_or_default(), value);
+        for pieces in patterns {
+            let _ = self.insert_variant(pieces, id);
+        }
+        id
+    }
+
+    /// Inserts the parsed pieces, the empty pieces set the value of the root.
+    ///
+    /// The pattern of an alias or a variant sets the value of its route and replaces its
+    /// pattern.
+    fn insert_parsed(&mut self, pieces: Vec<Piece>, value: T) -> usize {
+        let overwritten = !pieces.is_empty();
+        let node = self.node.insert_pieces(&pieces);
+
+        let bytes = static_bytes(&pieces);
+
+        let (id, slot) = match node.value {
+            Some(slot) if slot & (ALIAS | VARIANT) != 0 => {
+                let (id, pattern) = if slot & ALIAS == 0 {
+                    &mut self.variants[slot & !VARIANT]
+                } else {
+                    &mut self.aliases[slot & !ALIAS]
+                };
+                self.routes[*id].0 = value;
+                if overwritten {
+                    *pattern = pieces;
+                }
+                (*id, slot)
+            }
+            Some(id) => {
+                self.routes[id].0 = value;
+                if overwritten {
+                    self.routes[id].1 = pieces;
+                }
+                (id, id)
+            }
+            None => {
+                self.routes.push((value, pieces));
+                let id = self.id;
+                node.value = Some(id);
+                self.id += 1;
+                (id, id)
+            }
         };
 
-        if let Some(id) = node.value {
-            self.routes[id].0 = value;
-            if overwritten {
-                self.routes[id].1 = pieces;
+        let pieces = alias::pieces(&self.aliases, &self.variants, slot, &self.routes[id].1);
+
+        if let Some(prefilter) = &mut self.prefilter {
+            prefilter.insert(pieces);
+        }
+
+        // fully static routes are also indexed when the walk reaches them
+        if let Some(bytes) = bytes {
+            if self.node.find(&bytes).is_some_and(|(i, _)| *i == slot) {
+                self.statics.insert(&bytes, slot);
             }
-            id
-        } else {
-            self.routes.push((value, pieces));
-            let id = self.id;
-            node.value = Some(id);
-            self.id += 1;
-            id
         }
+
+        id
+    }
+
+    /// Inserts a path-value with a name, which [`PathTree::url_for_name`] refers to.
+    ///
+    /// A name inserted again refers to the new route.
+    #[must_use]
+    pub fn insert_named(&mut self, name: &str, path: &str, value: T) -> usize {
+        let id = self.insert(path, value);
+        self.names.insert(name.to_string(), id);
+        id
     }
 
     /// Returns the [`Path`] by the given path.
     #[must_use]
     pub fn find<'a, 'b>(&'a self, path: &'b str) -> Option<(&'a T, Path<'a, 'b>)> {
         let bytes = path.as_bytes();
-        self.node.find(bytes).and_then(|(id, ranges)| {
+        let mut ranges = SmallVec::new_const(); // opt!
+        self.find_id(bytes, &mut ranges).and_then(|slot| {
+            let (id, alias) = resolve(&self.aliases, &self.variants, slot);
             self.routes.get(*id).map(|(value, pieces)| {
                 (
                     value,
                     Path {
                         id,
-                        pieces,
+                        pieces: alias::pieces(&self.aliases, &self.variants, *slot, pieces),
                         // opt!
                         raws: ranges
                             .into_iter()
                             .filter_map(|r| from_utf8(&bytes[r]).ok())
                             .rev()
                             .collect(),
+                        query: None,
+                        alias,
                     },
                 )
             })
         })
     }
 
+    /// Returns the [`Path`] by the request target, e.g. `/users/1?tab=repos#top`.
+    ///
+    /// Only the path is matched, the raw query is kept on the [`Path`] and the fragment is
+    /// dropped.
+    #[must_use]
+    pub fn find_target<'a, 'b>(&'a self, target: &'b str) -> Option<(&'a T, Path<'a, 'b>)> {
+        let (path, query) = url::split_target(target);
+        self.find(path)
+            .map(|(value, path)| (value, Path { query, ..path }))
+    }
+
+    /// Finds the path and stores the parameters into the reusable [`Captures`].
+    ///
+    /// Once the captures have grown to the number of parameters, it does not allocate.
+    pub fn find_into<'a>(
+        &'a self,
+        path: &str,
+        captures: &mut Captures,
+    ) -> Option<(&'a T, &'a [Piece])> {
+        let slot = self.find_id(path.as_bytes(), &mut captures.ranges)?;
+        let (&id, _) = resolve(&self.aliases, &self.variants, slot);
+        captures.finish(id);
+        self.routes.get(id).map(|(value, pieces)| {
+            (
+                value,
+                alias::pieces(&self.aliases, &self.variants, *slot, pieces),
+            )
+        })
+    }
+
+    /// Finds the path and calls `f` with each parameter name and value, in order.
+    ///
+    /// It does not allocate for up to 8 parameters.
+    pub fn find_with<F>(&self, path: &str, mut f: F) -> Option<&T>
+    where
+        F: FnMut(&str, &str),
+    {
+        let mut captures = Captures::new();
+        let (value, pieces) = self.find_into(path, &mut captures)?;
+        captures.params(pieces, path).for_each(|(k, v)| f(k, v));
+        Some(value)
+    }
+
+    /// Returns the [`Path`] by the given path, with the value mutably.
+    #[must_use]
+    pub fn find_mut<'a, 'b>(&'a mut self, path: &'b str) -> Option<(&'a mut T, Path<'a, 'b>)> {
+        let bytes = path.as_bytes();
+        let mut ranges = SmallVec::new_const();
+        let Self {
+            routes,
+            statics,
+            prefilter,
+            aliases,
+            variants,
+            node,
+            ..
+        } = self;
+        let slot = find_id(
+            statics,
+            prefilter.as_ref(),
+            bytes,
+            &mut ranges,
+            |bytes, ranges| node.find_into(bytes, ranges),
+        )?;
+        let (id, alias) = resolve(aliases, variants, slot);
+        routes.get_mut(*id).map(|(value, pieces)| {
+            (
+                value,
+                Path {
+                    id,
+                    pieces: alias::pieces(aliases, variants, *slot, pieces),
+                    raws: ranges
+                        .into_iter()
+                        .filter_map(|r| from_utf8(&bytes[r]).ok())
+                        .rev()
+                        .collect(),
+                    query: None,
+                    alias,
+                },
+            )
+        })
+    }
+
+    #[inline]
+    fn find_id(&self, bytes: &[u8], ranges: &mut SmallVec<[Range<usize>; 8]>) -> Option<&usize> {
+        find_id(
+            &self.statics,
+            self.prefilter.as_ref(),
+            bytes,
+            ranges,
+            |bytes, ranges| self.node.find_into(bytes, ranges),
+        )
+    }
+
     /// Gets the route by id.
     #[must_use]
     #[inline]
@@ -237,33 +504,217 @@ impl<T> PathTree<T> {
         self.routes.get(index)
     }
 
+    /// Returns the id of the route inserted by the pattern.
+    ///
+    /// The pattern is walked by its pieces, so the parameter names are not compared, e.g.
+    /// `/users/:name` finds the route of `/users/:id`. The pattern of an alias finds its route,
+    /// and the groups are expanded like [`PathTree::insert`] to find the route of the first
+    /// pattern.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the pattern expands to more than 256 patterns.
+    #[must_use]
+    pub fn id_of(&self, pattern: &str) -> Option<usize> {
+        self.node
+            .get(&self.expand(pattern)[0])
+            .map(|slot| *resolve(&self.aliases, &self.variants, slot).0)
+    }
+
+    /// Returns the id and value of the route inserted by the pattern, see [`PathTree::id_of`].
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the pattern expands to more than 256 patterns.
+    #[must_use]
+    pub fn get_by_pattern(&self, pattern: &str) -> Option<(usize, &T)> {
+        self.id_of(pattern).map(|id| (id, &self.routes[id].0))
+    }
+
+    /// Gets the value of the route by id mutably, with its pieces.
+    #[must_use]
+    #[inline]
+    pub fn get_route_mut(&mut self, index: usize) -> Option<(&mut T, &[Piece])> {
+        self.routes
+            .get_mut(index)
+            .map(|(value, pieces)| (value, &pieces[..]))
+    }
+
+    /// Retains only the routes `f` returns `true` for, with the id, value and pieces.
+    ///
+    /// The removed routes are pruned from the nodes and the remaining ones are numbered again
+    /// in order, so their ids can change.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(usize, &mut T, &[Piece]) -> bool,
+    {
+        let mut ids = Vec::with_capacity(self.routes.len());
+        let mut id = 0;
+        let mut removed = Vec::new();
+        for (i, (value, pieces)) in self.routes.iter_mut().enumerate() {
+            if f(i, value, pieces) {
+                ids.push(id);
+                id += 1;
+            } else {
+                ids.push(usize::MAX);
+                removed.push(i);
+            }
+        }
+        if removed.is_empty() {
+            return;
+        }
+
+        // the aliases and the variants of the removed routes are removed too
+        let aliases = retain_patterns(&mut self.node, &mut self.aliases, &ids);
+        let variants = retain_patterns(&mut self.node, &mut self.variants, &ids);
+
+        for &i in &removed {
+            self.node.remove(&self.routes[i].1);
+        }
+        self.node.remap(&|slot| {
+            if slot & (ALIAS | VARIANT) == 0 {
+                ids[slot]
+            } else if slot & ALIAS == 0 {
+                VARIANT | variants[slot & !VARIANT]
+            } else {
+                ALIAS | aliases[slot & !ALIAS]
+            }
+        });
+
+        self.names.retain(|_, id| {
+            *id = ids[*id];
+            *id != usize::MAX
+        });
+
+        let mut ids = ids.into_iter();
+        self.routes.retain(|_| ids.next() != Some(usize::MAX));
+        self.id = self.routes.len();
+        self.statics = StaticIndex::new(
+            &self.node,
+            alias::patterns(&self.routes, &self.aliases, &self.variants),
+        );
+        if self.prefilter.is_some() {
+            self.prefilter = Some(Prefilter::new(
+                alias::patterns(&self.routes, &self.aliases, &self.variants)
+                    .map(|(_, pieces)| pieces),
+            ));
+        }
+    }
+
     /// Generates URL with the params.
+    ///
+    /// The params are pasted as they are and the missing ones are skipped, with the `/` of
+    /// the segment parameters left out at the end, see [`PathTree::try_url_for`] for the
+    /// checked and encoded one.
+    ///
+    /// If the route takes more params than given, without the empty ones at the end, the first
+    /// variant taking as many is generated, so the absent optional groups are left out, e.g.
+    /// `/archive/2024` for `/archive(/:year(/:month))`.
     #[must_use]
     pub fn url_for(&self, index: usize, params: &[&str]) -> Option<String> {
-        self.get_route(index).and_then(|(_, pieces)| {
-            let mut bytes = Vec::new();
-            let mut iter = params.iter();
-
-            for piece in pieces {
-                match piece {
-                    Piece::String(s) => {
-                        bytes.extend_from_slice(s);
-                    }
-                    Piece::Parameter(_, _) => {
-                        if let Some(s) = iter.next() {
-                            bytes.extend_from_slice(s.as_bytes());
-                        }
-                    }
-                }
-            }
+        let mut iter = params.iter();
+        self.variant(index, |pieces| url::arity(pieces) == url::given(params))
+            .map(|pieces| url::join(pieces, |_| iter.next().copied(), false))
+    }
 
-            from_utf8(&bytes).map(ToString::to_string).ok()
+    /// Generates URL of the named route with the params by name.
+    ///
+    /// The anonymous parameters are named by their kind and position, e.g. `*1` and `+2`. If
+    /// some of the route are not given, the first variant taking only given ones is generated.
+    #[must_use]
+    pub fn url_for_name(&self, name: &str, params: &[(&str, &str)]) -> Option<String> {
+        let id = *self.names.get(name)?;
+        let given = |pieces: &[Piece]| {
+            pieces
+                .iter()
+                .filter_map(piece_filter)
+                .all(|name| params.iter().any(|(k, v)| *k == name && !v.is_empty()))
+        };
+        self.variant(id, given).map(|pieces| {
+            url::join(
+                pieces,
+                |piece| {
+                    let name = piece_filter(piece)?;
+                    params.iter().find(|(k, _)| *k == name).map(|(_, v)| *v)
+                },
+                false,
+            )
         })
     }
 
     pub fn iter(&self) -> Iter<'_, (T, Vec<Piece>)> {
         self.routes.iter()
     }
+
+    /// Returns an iterator over the values of the routes mutably, with their pieces.
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut(self.routes.iter_mut())
+    }
+
+    /// Freezes the tree into a read-only [`FrozenPathTree`] with a flat node layout.
+    #[must_use]
+    pub fn freeze(self) -> FrozenPathTree<T> {
+        FrozenPathTree::new(
+            &self.node,
+            self.routes,
+            self.aliases,
+            self.variants,
+            self.statics,
+            self.prefilter,
+        )
+    }
+}
+
+/// Removes the patterns of the removed routes from the nodes, maps the ids of the others and
+/// returns the new indexes of the patterns.
+fn retain_patterns(
+    node: &mut Node<usize>,
+    patterns: &mut Vec<(usize, Vec<Piece>)>,
+    ids: &[usize],
+) -> Vec<usize> {
+    let mut index = 0;
+    let mut indexes = Vec::with_capacity(patterns.len());
+    for (id, pieces) in patterns.iter() {
+        if ids[*id] == usize::MAX {
+            indexes.push(usize::MAX);
+            node.remove(pieces);
+        } else {
+            indexes.push(index);
+            index += 1;
+        }
+    }
+    patterns.retain_mut(|(id, _)| {
+        *id = ids[*id];
+        *id != usize::MAX
+    });
+    indexes
+}
+
+/// Finds the bytes accepted by the prefilter in the static index, else with `walk`.
+#[inline]
+pub(crate) fn find_id<'a, F>(
+    statics: &'a StaticIndex,
+    prefilter: Option<&Prefilter>,
+    bytes: &[u8],
+    ranges: &mut SmallVec<[Range<usize>; 8]>,
+    walk: F,
+) -> Option<&'a usize>
+where
+    F: FnOnce(&[u8], &mut SmallVec<[Range<usize>; 8]>) -> Option<&'a usize>,
+{
+    ranges.clear();
+    if !prefilter.is_none_or(|p| p.accepts_bytes(bytes)) {
+        return None;
+    }
+    statics.get(bytes).or_else(|| walk(bytes, ranges))
+}
+
+fn parse(path: &str) -> Vec<Piece> {
+    if path.is_empty() {
+        Vec::new()
+    } else {
+        Parser::new(path).collect()
+    }
 }
 
 impl<'a, T> IntoIterator for &'a PathTree<T> {
@@ -275,12 +726,51 @@ impl<'a, T> IntoIterator for &'a PathTree<T> {
     }
 }
 
+impl<'a, T> IntoIterator for &'a mut PathTree<T> {
+    type Item = (&'a mut T, &'a [Piece]);
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// A mutable iterator over the routes of a [`PathTree`], see [`PathTree::iter_mut`].
+#[derive(Debug)]
+pub struct IterMut<'a, T>(core::slice::IterMut<'a, (T, Vec<Piece>)>);
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = (&'a mut T, &'a [Piece]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(value, pieces)| (value, &pieces[..]))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<T> DoubleEndedIterator for IterMut<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0
+            .next_back()
+            .map(|(value, pieces)| (value, &pieces[..]))
+    }
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+
 /// Matched route path infomation.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Path<'a, 'b> {
     pub id: &'a usize,
     pub pieces: &'a [Piece],
     pub raws: SmallVec<[&'b str; 4]>,
+    /// The raw query of the request target, see [`PathTree::find_target`]
+    pub query: Option<&'b str>,
+    /// The index of the matched alias, see [`PathTree::insert_alias`]
+    pub alias: Option<usize>,
 }
 
 impl Path<'_, '_> {
@@ -325,6 +815,24 @@ impl Path<'_, '_> {
                     }
                 },
             },
+            Piece::Alternation(p, literals) => {
+                bytes.push(b':');
+                let (Position::Index(_, n) | Position::Named(n)) = p;
+                bytes.extend_from_slice(n);
+                bytes.push(b'(');
+                for (i, literal) in literals.iter().enumerate() {
+                    if i > 0 {
+                        bytes.push(b'|');
+                    }
+                    for &b in literal {
+                        if matches!(b, b'|' | b')' | b'\\') {
+                            bytes.push(b'\\');
+                        }
+                        bytes.push(b);
+                    }
+                }
+                bytes.push(b')');
+            }
         });
 
         from_utf8(&bytes)
@@ -332,6 +840,26 @@ impl Path<'_, '_> {
             .expect("pattern generated failure")
     }
 
+    /// Generates URL of the matched route with the current params, replacing the given ones
+    /// by name, see [`PathTree::url_for_name`].
+    ///
+    /// The query is not included.
+    #[must_use]
+    pub fn url_with(&self, params: &[(&str, &str)]) -> String {
+        let mut raws = self.raws.iter();
+        url::join(
+            self.pieces,
+            |piece| {
+                let raw = raws.next().copied();
+                piece_filter(piece)
+                    .and_then(|name| params.iter().find(|(k, _)| *k == name))
+                    .map(|(_, v)| *v)
+                    .or(raw)
+            },
+            false,
+        )
+    }
+
     /// Returns the parameters of the current path.
     #[must_use]
     pub fn params(&self) -> Vec<(&str, &str)> {
@@ -340,20 +868,87 @@ impl Path<'_, '_> {
 
     /// Returns the parameters iterator of the current path.
     pub fn params_iter(&self) -> impl Iterator<Item = (&str, &str)> {
-        #[inline]
-        fn piece_filter(piece: &Piece) -> Option<&str> {
-            match piece {
-                Piece::String(_) => None,
-                Piece::Parameter(p, _) => from_utf8(match p {
-                    Position::Index(_, n) | Position::Named(n) => n,
-                })
-                .ok(),
-            }
-        }
-
         self.pieces
             .iter()
             .filter_map(piece_filter)
             .zip(self.raws.iter().copied())
     }
 }
+
+#[inline]
+fn piece_filter(piece: &Piece) -> Option<&str> {
+    match piece {
+        Piece::String(_) => None,
+        Piece::Parameter(p, _) | Piece::Alternation(p, _) => from_utf8(match p {
+            Position::Index(_, n) | Position::Named(n) => n,
+        })
+        .ok(),
+    }
+}
+
+/// A reusable buffer of the parameters captured by [`PathTree::find_into`].
+#[derive(Clone, Debug, Default)]
+pub struct Captures {
+    id: usize,
+    ranges: SmallVec<[Range<usize>; 8]>,
+}
+
+impl Captures {
+    /// Creates a new empty [`Captures`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the id of the last matched route.
+    #[must_use]
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    /// Returns the number of captured parameters.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.ranges.len()
+    }
+
+    /// Returns `true` if no parameters were captured.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Returns the byte ranges of the parameters in the matched path, in order.
+    #[must_use]
+    pub fn ranges(&self) -> &[Range<usize>] {
+        &self.ranges
+    }
+
+    /// Returns the value of the parameter at the index in the matched path.
+    #[must_use]
+    pub fn get<'p>(&self, index: usize, path: &'p str) -> Option<&'p str> {
+        self.ranges
+            .get(index)
+            .and_then(|r| path.get(r.start..r.end))
+    }
+
+    /// Returns the parameters iterator, with the pieces of the matched route and the matched path.
+    pub fn params<'a, 'p>(
+        &'a self,
+        pieces: &'a [Piece],
+        path: &'p str,
+    ) -> impl Iterator<Item = (&'a str, &'p str)> {
+        pieces
+            .iter()
+            .filter_map(piece_filter)
+            .zip(self.ranges.iter())
+            .filter_map(|(k, r)| path.get(r.start..r.end).map(|v| (k, v)))
+    }
+
+    #[inline]
+    fn finish(&mut self, id: usize) {
+        self.id = id;
+        // the ranges are pushed from the last one
+        self.ranges.reverse();
+    }
+}
@@ -7,12 +7,14 @@ use core::{
 
 use smallvec::SmallVec;
 
-use crate::Kind;
+use crate::{walk, Kind, Piece};
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum Key {
     String(Vec<u8>),
     Parameter(Kind),
+    /// Matches one of the literals, tried before the parameters
+    Alternation(Vec<Vec<u8>>),
 }
 
 #[derive(Clone)]
@@ -35,57 +37,166 @@ impl<T: fmt::Debug> Node<T> {
         }
     }
 
-    pub fn insert_bytes(&mut self, mut bytes: &[u8]) -> &mut Self {
-        let diff = match &mut self.key {
-            Key::String(s) => {
-                if s.is_empty() {
-                    *s = bytes.to_vec();
-                    return self;
-                }
+    pub fn insert_bytes(&mut self, bytes: &[u8]) -> &mut Self {
+        RadixNode::insert_bytes(self, bytes)
+    }
 
-                let cursor = s
-                    .iter()
-                    .zip(bytes.iter())
-                    .take_while(|(a, b)| a == b)
-                    .count();
+    pub fn insert_parameter(&mut self, kind: Kind) -> &mut Self {
+        RadixNode::insert_parameter(self, kind)
+    }
 
-                if cursor == 0 {
-                    true
-                } else {
-                    // split node
-                    if cursor < s.len() {
-                        let (prefix, suffix) = s.split_at(cursor);
-                        let mut node = Node::new(Key::String(prefix.to_vec()), None);
-                        *s = suffix.to_vec();
-                        ::core::mem::swap(self, &mut node);
-                        self.nodes0.get_or_insert_with(Vec::new).push(node);
+    pub fn find(&self, bytes: &[u8]) -> Option<(&T, SmallVec<[Range<usize>; 8]>)> {
+        let mut ranges = SmallVec::<[Range<usize>; 8]>::new_const(); // opt!
+        walk::find_with(self, 0, bytes, &mut ranges).map(|t| (t, ranges))
+    }
+}
+
+impl Node<usize> {
+    /// Maps the values of the nodes with `f`.
+    pub(crate) fn remap<F: Fn(usize) -> usize>(&mut self, f: &F) {
+        if let Some(id) = &mut self.value {
+            *id = f(*id);
+        }
+        for node in self
+            .nodes0
+            .iter_mut()
+            .chain(self.nodes1.iter_mut())
+            .flatten()
+        {
+            node.remap(f);
+        }
+    }
+}
+
+impl<T> RadixNode for Node<T> {
+    type Value = T;
+
+    fn leaf(key: Key) -> Self {
+        Self {
+            key,
+            value: None,
+            nodes0: None,
+            nodes1: None,
+        }
+    }
+
+    #[inline]
+    fn key(&self) -> &Key {
+        &self.key
+    }
+
+    #[inline]
+    fn value(&self) -> Option<&T> {
+        self.value.as_ref()
+    }
+
+    #[inline]
+    fn nodes0(&self) -> Option<&[Self]> {
+        self.nodes0.as_deref()
+    }
+
+    #[inline]
+    fn nodes1(&self) -> Option<&[Self]> {
+        self.nodes1.as_deref()
+    }
+
+    fn parts_mut(&mut self) -> Parts<'_, Self> {
+        Parts {
+            key: &mut self.key,
+            value: &mut self.value,
+            nodes0: &mut self.nodes0,
+            nodes1: &mut self.nodes1,
+        }
+    }
+}
+
+/// The fields of a node, borrowed for building it.
+pub(crate) struct Parts<'a, N: RadixNode> {
+    pub(crate) key: &'a mut Key,
+    pub(crate) value: &'a mut Option<N::Value>,
+    pub(crate) nodes0: &'a mut Option<Vec<N>>,
+    pub(crate) nodes1: &'a mut Option<Vec<N>>,
+}
+
+/// A node of the radix tree, built and walked the same way whether it is owned by a [`Node`]
+/// or shared between the versions of a [`PersistentPathTree`](crate::PersistentPathTree).
+pub(crate) trait RadixNode: Sized {
+    type Value;
+
+    fn leaf(key: Key) -> Self;
+
+    fn key(&self) -> &Key;
+
+    fn value(&self) -> Option<&Self::Value>;
+
+    fn nodes0(&self) -> Option<&[Self]>;
+
+    fn nodes1(&self) -> Option<&[Self]>;
+
+    /// Borrows the fields to modify them, a shared node is copied first.
+    fn parts_mut(&mut self) -> Parts<'_, Self>;
+
+    fn insert_bytes(&mut self, mut bytes: &[u8]) -> &mut Self {
+        let (diff, split) = {
+            let node = self.parts_mut();
+            match node.key {
+                Key::String(s) => {
+                    if s.is_empty() {
+                        *s = bytes.to_vec();
+                        return self;
                     }
-                    if cursor == bytes.len() {
-                        false
+
+                    let cursor = s
+                        .iter()
+                        .zip(bytes.iter())
+                        .take_while(|(a, b)| a == b)
+                        .count();
+
+                    if cursor == 0 {
+                        (true, None)
                     } else {
-                        bytes = &bytes[cursor..];
-                        true
+                        // split node
+                        let split = (cursor < s.len()).then(|| {
+                            let prefix = s[..cursor].to_vec();
+                            *s = s[cursor..].to_vec();
+                            prefix
+                        });
+                        if cursor == bytes.len() {
+                            (false, split)
+                        } else {
+                            bytes = &bytes[cursor..];
+                            (true, split)
+                        }
                     }
                 }
+                Key::Parameter(_) | Key::Alternation(_) => (true, None),
             }
-            Key::Parameter(_) => true,
         };
 
+        if let Some(prefix) = split {
+            let mut node = Self::leaf(Key::String(prefix));
+            ::core::mem::swap(self, &mut node);
+            self.parts_mut()
+                .nodes0
+                .get_or_insert_with(Vec::new)
+                .push(node);
+        }
+
         // insert node
         if diff {
-            let nodes = self.nodes0.get_or_insert_with(Vec::new);
-            return match nodes.binary_search_by(|node| match &node.key {
+            let nodes = self.parts_mut().nodes0.get_or_insert_with(Vec::new);
+            return match nodes.binary_search_by(|node| match node.key() {
                 Key::String(s) => {
                     // s[0].cmp(&bytes[0])
                     // opt!
                     // lets `/` at end
                     compare(s[0], bytes[0])
                 }
-                Key::Parameter(_) => unreachable!(),
+                Key::Parameter(_) | Key::Alternation(_) => unreachable!(),
             }) {
                 Ok(i) => nodes[i].insert_bytes(bytes),
                 Err(i) => {
-                    nodes.insert(i, Node::new(Key::String(bytes.to_vec()), None));
+                    nodes.insert(i, Self::leaf(Key::String(bytes.to_vec())));
                     &mut nodes[i]
                 }
             };
@@ -94,295 +205,175 @@ impl<T: fmt::Debug> Node<T> {
         self
     }
 
-    pub fn insert_parameter(&mut self, kind: Kind) -> &mut Self {
-        let nodes = self.nodes1.get_or_insert_with(Vec::new);
+    fn insert_parameter(&mut self, kind: Kind) -> &mut Self {
+        self.insert_key(Key::Parameter(kind))
+    }
+
+    /// Inserts the parameter node of the key.
+    fn insert_key(&mut self, key: Key) -> &mut Self {
+        let nodes = self.parts_mut().nodes1.get_or_insert_with(Vec::new);
         let i = nodes
-            .binary_search_by(|node| match node.key {
-                Key::Parameter(pk) => pk.cmp(&kind),
-                Key::String(_) => unreachable!(),
-            })
+            .binary_search_by(|node| compare_parameters(node.key(), &key))
             .unwrap_or_else(|i| {
-                nodes.insert(i, Node::new(Key::Parameter(kind), None));
+                nodes.insert(i, Self::leaf(key));
                 i
             });
         &mut nodes[i]
     }
 
-    #[allow(clippy::range_plus_one)]
-    #[allow(clippy::too_many_lines)]
-    #[inline]
-    fn find_with(
-        &self,
-        mut start: usize,
-        mut bytes: &[u8],
-        ranges: &mut SmallVec<[Range<usize>; 8]>,
-    ) -> Option<&T> {
-        let mut m = bytes.len();
-        match &self.key {
-            Key::String(s) => {
-                let n = s.len();
-                let mut flag = m >= n;
-
-                // opt!
-                if flag {
-                    if n == 1 {
-                        flag = s[0] == bytes[0];
-                    } else {
-                        flag = s == &bytes[..n];
-                    }
-                }
-
-                // starts with prefix
-                if flag {
-                    m -= n;
-                    start += n;
-                    bytes = &bytes[n..];
+    /// Inserts the pieces and returns the last node, the consecutive strings (escapes) as one.
+    fn insert_pieces(&mut self, pieces: &[Piece]) -> &mut Self {
+        match pieces.split_first() {
+            Some((Piece::String(s), rest)) => {
+                let (bytes, rest) = concat(s, rest);
+                self.insert_bytes(bytes.as_deref().unwrap_or(s))
+                    .insert_pieces(rest)
+            }
+            Some((piece, rest)) => self.insert_key(parameter_key(piece)).insert_pieces(rest),
+            None => self,
+        }
+    }
 
-                    if m == 0 {
-                        if let Some(id) = &self.value {
-                            return Some(id);
-                        }
-                    } else {
-                        // static
-                        if let Some(id) = self.nodes0.as_ref().and_then(|nodes| {
-                            nodes
-                                .binary_search_by(|node| match &node.key {
-                                    Key::String(s) => {
-                                        // s[0].cmp(&bytes[0])
-                                        // opt!
-                                        // lets `/` at end
-                                        compare(s[0], bytes[0])
-                                    }
-                                    Key::Parameter(_) => unreachable!(),
-                                })
-                                .ok()
-                                .and_then(|i| nodes[i].find_with(start, bytes, ranges))
-                        }) {
-                            return Some(id);
-                        }
-                    }
+    /// Returns the value of the node the pieces were inserted at.
+    fn get(&self, pieces: &[Piece]) -> Option<&Self::Value> {
+        match pieces.split_first() {
+            Some((Piece::String(s), rest)) => {
+                let (bytes, rest) = concat(s, rest);
+                self.get_bytes(bytes.as_deref().unwrap_or(s), rest)
+            }
+            Some((piece, rest)) => {
+                let nodes = self.nodes1()?;
+                let key = parameter_key(piece);
+                nodes
+                    .binary_search_by(|node| compare_parameters(node.key(), &key))
+                    .ok()
+                    .and_then(|i| nodes[i].get(rest))
+            }
+            None => self.value(),
+        }
+    }
 
-                    // parameter
-                    if let Some(id) = self.nodes1.as_ref().and_then(|nodes| {
-                        let b = m > 0;
-                        nodes
-                            .iter()
-                            .filter(|node| match node.key {
-                                Key::Parameter(pk)
-                                    if pk == Kind::Normal || pk == Kind::OneOrMore =>
-                                {
-                                    b
-                                }
-                                _ => true,
-                            })
-                            .find_map(|node| node.find_with(start, bytes, ranges))
-                    }) {
-                        return Some(id);
-                    }
-                } else if n == 1 && s[0] == b'/' {
-                    if let Some(id) = self.nodes1.as_ref().and_then(|nodes| {
-                        nodes
-                            .iter()
-                            .filter(|node| {
-                                matches!(node.key,
-                                    Key::Parameter(pk)
-                                        if pk == Kind::OptionalSegment
-                                            || pk == Kind::ZeroOrMoreSegment
-                                )
-                            })
-                            .find_map(|node| node.find_with(start, bytes, ranges))
-                    }) {
-                        return Some(id);
-                    }
+    /// Follows the same nodes as [`RadixNode::insert_bytes`].
+    fn get_bytes(&self, mut bytes: &[u8], rest: &[Piece]) -> Option<&Self::Value> {
+        if let Key::String(s) = self.key() {
+            let cursor = s
+                .iter()
+                .zip(bytes.iter())
+                .take_while(|(a, b)| a == b)
+                .count();
+
+            if cursor > 0 {
+                if cursor < s.len() {
+                    return None;
                 }
+                if cursor == bytes.len() {
+                    return self.get(rest);
+                }
+                bytes = &bytes[cursor..];
             }
-            Key::Parameter(k) => match k {
-                Kind::Normal | Kind::Optional | Kind::OptionalSegment => {
-                    if m == 0 {
-                        if k == &Kind::Normal {
-                            return None;
-                        }
-
-                        // last
-                        if self.nodes0.is_none() && self.nodes1.is_none() {
-                            return self.value.as_ref().inspect(|_| {
-                                ranges.push(start..start);
-                            });
-                        }
-                    } else {
-                        // static
-                        if let Some(id) = self.nodes0.as_ref().and_then(|nodes| {
-                            nodes.iter().find_map(|node| match &node.key {
-                                Key::String(s) => {
-                                    let mut keep_running = true;
-                                    bytes
-                                        .iter()
-                                        // as it turns out doing .copied() here is much slower than dereferencing in the closure
-                                        // https://godbolt.org/z/7dnW91T1Y
-                                        .take_while(|b| {
-                                            if keep_running && **b == b'/' {
-                                                keep_running = false;
-                                                true
-                                            } else {
-                                                keep_running
-                                            }
-                                        })
-                                        .enumerate()
-                                        .filter_map(|(n, b)| (s[0] == *b).then_some(n))
-                                        .find_map(|n| {
-                                            node.find_with(start + n, &bytes[n..], ranges).inspect(
-                                                |_| {
-                                                    ranges.push(start..start + n);
-                                                },
-                                            )
-                                        })
-                                }
-                                Key::Parameter(_) => unreachable!(),
-                            })
-                        }) {
-                            return Some(id);
-                        }
-
-                        // parameter => `:a:b:c`
-                        if let Some(id) = self.nodes1.as_ref().and_then(|nodes| {
-                            let b = m - 1 > 0;
-                            nodes
-                                .iter()
-                                .filter(|node| match node.key {
-                                    Key::Parameter(pk)
-                                        if pk == Kind::Normal || pk == Kind::OneOrMore =>
-                                    {
-                                        b
-                                    }
-                                    _ => true,
-                                })
-                                .find_map(|node| node.find_with(start + 1, &bytes[1..], ranges))
-                        }) {
-                            ranges.push(start..start + 1);
-                            return Some(id);
-                        }
-                    }
+        }
 
-                    // parameter => `:a:b?:c?`
-                    if k == &Kind::Optional || k == &Kind::OptionalSegment {
-                        if let Some(id) = self.nodes1.as_ref().and_then(|nodes| {
-                            let b = m > 0;
-                            nodes
-                                .iter()
-                                .filter(|node| match &node.key {
-                                    Key::Parameter(pk)
-                                        if pk == &Kind::Normal || pk == &Kind::OneOrMore =>
-                                    {
-                                        b
-                                    }
-                                    _ => true,
-                                })
-                                .find_map(|node| node.find_with(start, bytes, ranges))
-                        }) {
-                            // param should be empty
-                            ranges.push(start + m..start + m);
-                            return Some(id);
-                        }
-                    }
+        let nodes = self.nodes0()?;
+        nodes
+            .binary_search_by(|node| match node.key() {
+                Key::String(s) => compare(s[0], bytes[0]),
+                Key::Parameter(_) | Key::Alternation(_) => unreachable!(),
+            })
+            .ok()
+            .and_then(|i| nodes[i].get_bytes(bytes, rest))
+    }
 
-                    if let Some(n) = bytes.iter().position(|b| *b == b'/') {
-                        bytes = &bytes[n..];
-                    } else {
-                        if let Some(id) = &self.value {
-                            ranges.push(start..start + m);
-                            return Some(id);
-                        }
-                        bytes = &bytes[m..];
-                    }
+    /// Takes the value of the node the pieces were inserted at, pruning the nodes left empty
+    /// and merging the string nodes left with one string child.
+    fn remove(&mut self, pieces: &[Piece]) -> Option<Self::Value> {
+        let value = match pieces.split_first() {
+            Some((Piece::String(s), rest)) => {
+                let (bytes, rest) = concat(s, rest);
+                return self.remove_bytes(bytes.as_deref().unwrap_or(s), rest);
+            }
+            Some((piece, rest)) => {
+                let nodes = self.parts_mut().nodes1;
+                let key = parameter_key(piece);
+                let i = nodes
+                    .as_ref()?
+                    .binary_search_by(|node| compare_parameters(node.key(), &key))
+                    .ok()?;
+                let value = nodes.as_mut()?[i].remove(rest)?;
+                prune(nodes, i);
+                value
+            }
+            None => self.parts_mut().value.take()?,
+        };
+        self.compact();
+        Some(value)
+    }
 
-                    if k == &Kind::OptionalSegment {
-                        if let Some(id) = self.nodes0.as_ref().and_then(|nodes| {
-                            nodes
-                                .last()
-                                .filter(|node| match &node.key {
-                                    Key::String(s) => s[0] == b'/',
-                                    Key::Parameter(_) => unreachable!(),
-                                })
-                                .and_then(|node| node.find_with(start, bytes, ranges))
-                        }) {
-                            ranges.push(start..start + m);
-                            return Some(id);
-                        }
-                    }
+    /// Follows the same nodes as [`RadixNode::insert_bytes`].
+    fn remove_bytes(&mut self, mut bytes: &[u8], rest: &[Piece]) -> Option<Self::Value> {
+        if let Key::String(s) = self.key() {
+            let cursor = s
+                .iter()
+                .zip(bytes.iter())
+                .take_while(|(a, b)| a == b)
+                .count();
+
+            if cursor > 0 {
+                if cursor < s.len() {
+                    return None;
                 }
-                Kind::OneOrMore | Kind::ZeroOrMore | Kind::ZeroOrMoreSegment => {
-                    let is_one_or_more = k == &Kind::OneOrMore;
-                    if m == 0 {
-                        if is_one_or_more {
-                            return None;
-                        }
-
-                        if self.nodes0.is_none() && self.nodes1.is_none() {
-                            return self.value.as_ref().inspect(|_| {
-                                ranges.push(start..start);
-                            });
-                        }
-                    } else {
-                        if self.nodes0.is_none() && self.nodes1.is_none() {
-                            if let Some(id) = &self.value {
-                                ranges.push(start..start + m);
-                                return Some(id);
-                            }
-                        }
+                if cursor == bytes.len() {
+                    return self.remove(rest);
+                }
+                bytes = &bytes[cursor..];
+            }
+        }
 
-                        // static
-                        if let Some(id) = self.nodes0.as_ref().and_then(|nodes| {
-                            nodes.iter().find_map(|node| {
-                                if let Key::String(s) = &node.key {
-                                    let right_length = if is_one_or_more {
-                                        m > s.len()
-                                    } else {
-                                        m >= s.len()
-                                    };
-                                    if right_length {
-                                        return bytes
-                                            .iter()
-                                            .enumerate()
-                                            .filter_map(|(n, b)| (s[0] == *b).then_some(n))
-                                            .find_map(|n| {
-                                                node.find_with(start + n, &bytes[n..], ranges)
-                                                    .inspect(|_| {
-                                                        ranges.push(start..start + n);
-                                                    })
-                                            });
-                                    }
-                                }
-                                None
-                            })
-                        }) {
-                            return Some(id);
-                        }
-                    }
+        let nodes = self.parts_mut().nodes0;
+        let i = nodes
+            .as_ref()?
+            .binary_search_by(|node| match node.key() {
+                Key::String(s) => compare(s[0], bytes[0]),
+                Key::Parameter(_) | Key::Alternation(_) => unreachable!(),
+            })
+            .ok()?;
+        let value = nodes.as_mut()?[i].remove_bytes(bytes, rest)?;
+        prune(nodes, i);
+        self.compact();
+        Some(value)
+    }
 
-                    if k == &Kind::ZeroOrMoreSegment {
-                        if let Some(id) = self.nodes0.as_ref().and_then(|nodes| {
-                            nodes
-                                .last()
-                                .filter(|node| match &node.key {
-                                    Key::String(s) => s[0] == b'/',
-                                    Key::Parameter(_) => unreachable!(),
-                                })
-                                .and_then(|node| node.find_with(start, bytes, ranges))
-                        }) {
-                            // param should be empty
-                            ranges.push(start + m..start + m);
-                            return Some(id);
-                        }
-                    }
+    /// Merges the only string child into a string node left without a value and parameters.
+    fn compact(&mut self) {
+        let node = self.parts_mut();
+        if node.value.is_none()
+            && node.nodes1.is_none()
+            && node.nodes0.as_ref().is_some_and(|nodes| nodes.len() == 1)
+        {
+            if let Key::String(s) = node.key {
+                let mut child = node
+                    .nodes0
+                    .take()
+                    .and_then(|mut nodes| nodes.pop())
+                    .unwrap();
+                let child = child.parts_mut();
+                if let Key::String(suffix) = child.key {
+                    s.extend_from_slice(suffix);
                 }
-            },
+                *node.value = child.value.take();
+                *node.nodes0 = child.nodes0.take();
+                *node.nodes1 = child.nodes1.take();
+            }
         }
-        None
     }
 
-    pub fn find(&self, bytes: &[u8]) -> Option<(&T, SmallVec<[Range<usize>; 8]>)> {
-        let mut ranges = SmallVec::<[Range<usize>; 8]>::new_const(); // opt!
-        self.find_with(0, bytes, &mut ranges).map(|t| (t, ranges))
+    /// Finds the bytes, pushing the ranges of the parameters from the last one.
+    #[inline]
+    fn find_into(
+        &self,
+        bytes: &[u8],
+        ranges: &mut SmallVec<[Range<usize>; 8]>,
+    ) -> Option<&Self::Value> {
+        walk::find_with(self, 0, bytes, ranges)
     }
 }
 
@@ -457,6 +448,16 @@ impl<T: fmt::Debug> fmt::Debug for Node<T> {
                     };
                     f.write_char(c)?;
                 }
+                Key::Alternation(literals) => {
+                    f.write_char('(')?;
+                    for (i, literal) in literals.iter().enumerate() {
+                        if i > 0 {
+                            f.write_char('|')?;
+                        }
+                        f.write_str(&String::from_utf8_lossy(literal))?;
+                    }
+                    f.write_char(')')?;
+                }
             }
             if let Some(value) = &node.value {
                 f.write_str(" •")?;
@@ -481,8 +482,60 @@ impl<T: fmt::Debug> fmt::Debug for Node<T> {
     }
 }
 
+/// Joins the string with the strings following it, returning the rest of the pieces.
+fn concat<'a>(s: &[u8], rest: &'a [Piece]) -> (Option<Vec<u8>>, &'a [Piece]) {
+    let n = rest
+        .iter()
+        .take_while(|piece| matches!(piece, Piece::String(_)))
+        .count();
+    if n == 0 {
+        return (None, rest);
+    }
+
+    let mut bytes = s.to_vec();
+    for piece in &rest[..n] {
+        if let Piece::String(s) = piece {
+            bytes.extend_from_slice(s);
+        }
+    }
+    (Some(bytes), &rest[n..])
+}
+
+/// Returns the key of the parameter node of the piece.
+pub(crate) fn parameter_key(piece: &Piece) -> Key {
+    match piece {
+        Piece::Parameter(_, kind) => Key::Parameter(*kind),
+        Piece::Alternation(_, literals) => Key::Alternation(literals.clone()),
+        Piece::String(_) => unreachable!(),
+    }
+}
+
+/// Orders the parameter nodes, the alternations before the parameters.
+pub(crate) fn compare_parameters(a: &Key, b: &Key) -> Ordering {
+    match (a, b) {
+        (Key::Parameter(a), Key::Parameter(b)) => a.cmp(b),
+        (Key::Alternation(a), Key::Alternation(b)) => a.cmp(b),
+        (Key::Alternation(_), Key::Parameter(_)) => Ordering::Less,
+        (Key::Parameter(_), Key::Alternation(_)) => Ordering::Greater,
+        (Key::String(_), _) | (_, Key::String(_)) => unreachable!(),
+    }
+}
+
+/// Drops the child left without a value and children, and the list left empty.
+fn prune<N: RadixNode>(nodes: &mut Option<Vec<N>>, i: usize) {
+    if let Some(list) = nodes {
+        let node = &list[i];
+        if node.value().is_none() && node.nodes0().is_none() && node.nodes1().is_none() {
+            list.remove(i);
+        }
+        if list.is_empty() {
+            *nodes = None;
+        }
+    }
+}
+
 #[inline]
-fn compare(a: u8, b: u8) -> Ordering {
+pub(crate) fn compare(a: u8, b: u8) -> Ordering {
     if a == b {
         Ordering::Equal
     } else if a == b'/' {
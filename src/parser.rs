@@ -1,4 +1,7 @@
-use alloc::{string::ToString, vec::Vec};
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
 use core::{iter::Peekable, str::CharIndices};
 
 #[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
@@ -26,6 +29,8 @@ pub enum Kind {
 pub enum Piece {
     String(Vec<u8>),
     Parameter(Position, Kind),
+    /// A parameter matching one of the literals: `:name(a|b)`
+    Alternation(Position, Vec<Vec<u8>>),
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -52,6 +57,34 @@ impl<'a> Parser<'a> {
         }
     }
 
+    /// Expands the brace groups of the pattern in order, e.g. `/v{1,2}/users` to `/v1/users`
+    /// and `/v2/users`.
+    ///
+    /// The groups can be nested. A group without a `,` or a closing `}` is kept as it is, like
+    /// the escaped `\{` and `\}`.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the pattern expands to more than 256 patterns.
+    #[must_use]
+    pub fn expand(input: &str) -> Vec<String> {
+        expand(input, false)
+    }
+
+    /// Expands the brace groups like [`Parser::expand`], and the optional groups present then
+    /// absent, e.g. `/report(.:format)` to `/report.:format` and `/report`.
+    ///
+    /// A group without a closing `)` is kept as it is, like the escaped `\(` and `\)` or an
+    /// alternation `:name(a|b)`.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the pattern expands to more than 256 patterns.
+    #[must_use]
+    pub fn expand_optional(input: &str) -> Vec<String> {
+        expand(input, true)
+    }
+
     fn string(&mut self) -> &'a [u8] {
         let mut start = self.pos;
         while let Some(&(i, c)) = self.cursor.peek() {
@@ -91,7 +124,7 @@ impl<'a> Parser<'a> {
         let start = self.pos;
         while let Some(&(i, c)) = self.cursor.peek() {
             match c {
-                '-' | '.' | '~' | '/' | '\\' | ':' => {
+                '-' | '.' | '~' | '/' | '\\' | ':' | '(' => {
                     self.pos = i;
                     return (
                         Position::Named(self.input.as_bytes()[start..i].to_vec()),
@@ -137,6 +170,177 @@ impl<'a> Parser<'a> {
             Kind::Normal,
         )
     }
+
+    /// Parses the literals of `(a|b)` after a parameter, a group with a `/` or a parameter in
+    /// it is not an alternation.
+    fn alternation(&mut self) -> Option<Vec<Vec<u8>>> {
+        let mut cursor = self.cursor.clone();
+        if cursor.next()?.1 != '(' {
+            return None;
+        }
+
+        let mut literals = Vec::new();
+        let mut literal = Vec::new();
+        while let Some((i, c)) = cursor.next() {
+            match c {
+                '\\' => {
+                    let (j, c) = cursor.next()?;
+                    literal.extend_from_slice(&self.input.as_bytes()[j..j + c.len_utf8()]);
+                }
+                '|' => literals.push(core::mem::take(&mut literal)),
+                ')' => {
+                    literals.push(literal);
+                    self.cursor = cursor;
+                    self.pos = i + 1;
+                    return Some(literals);
+                }
+                '/' | ':' | '+' | '*' | '(' => return None,
+                _ => literal.extend_from_slice(&self.input.as_bytes()[i..i + c.len_utf8()]),
+            }
+        }
+        None
+    }
+}
+
+/// The most patterns a pattern expands to.
+const MAX_PATTERNS: usize = 256;
+
+/// Expands the brace groups, and the optional groups if `optional`.
+///
+/// Panics if the pattern expands to more than [`MAX_PATTERNS`] patterns.
+pub(crate) fn expand(input: &str, optional: bool) -> Vec<String> {
+    assert!(
+        count(input, optional) <= MAX_PATTERNS,
+        "the pattern `{input}` expands to more than {MAX_PATTERNS} patterns"
+    );
+    expand_groups(input, optional)
+}
+
+/// Returns the number of patterns the groups expand to, saturated at `usize::MAX`.
+fn count(input: &str, optional: bool) -> usize {
+    let Some((open, close, commas)) = first_group(input.as_bytes(), optional) else {
+        return 1;
+    };
+
+    let mut n = usize::from(input.as_bytes()[open] == b'(');
+    let mut start = open + 1;
+    for end in commas.into_iter().chain([close]) {
+        n = n.saturating_add(count(&input[start..end], optional));
+        start = end + 1;
+    }
+    n.saturating_mul(count(&input[close + 1..], optional))
+}
+
+fn expand_groups(input: &str, optional: bool) -> Vec<String> {
+    let Some((open, close, commas)) = first_group(input.as_bytes(), optional) else {
+        return alloc::vec![input.to_string()];
+    };
+
+    let mut alternatives = Vec::new();
+    let mut start = open + 1;
+    for end in commas.into_iter().chain([close]) {
+        alternatives.push(&input[start..end]);
+        start = end + 1;
+    }
+    if input.as_bytes()[open] == b'(' {
+        alternatives.push("");
+    }
+
+    let prefix = &input[..open];
+    let suffixes = expand_groups(&input[close + 1..], optional);
+    let mut patterns = Vec::new();
+    for alternative in alternatives {
+        for alternative in expand_groups(alternative, optional) {
+            for suffix in &suffixes {
+                patterns.push([prefix, &alternative, suffix].concat());
+            }
+        }
+    }
+    patterns
+}
+
+/// Returns `true` if the pattern has groups to expand, see [`Parser::expand`] and
+/// [`Parser::expand_optional`].
+pub(crate) fn has_groups(input: &str, optional: bool) -> bool {
+    (input.contains('{') || optional && input.contains('('))
+        && first_group(input.as_bytes(), optional).is_some()
+}
+
+/// Finds the first group to expand, by the `{` or the `(` if `optional`, the closing `}` or
+/// `)` and the `,` between the alternatives of a brace group.
+fn first_group(bytes: &[u8], optional: bool) -> Option<(usize, usize, Vec<usize>)> {
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 1,
+            b'{' => {
+                if let Some((close, commas)) = group(bytes, i).filter(|(_, c)| !c.is_empty()) {
+                    return Some((i, close, commas));
+                }
+            }
+            b'(' if optional => {
+                if let Some((close, _)) = group(bytes, i) {
+                    if !is_alternation(bytes, i, close) {
+                        return Some((i, close, Vec::new()));
+                    }
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Finds the `}` or `)` closing the group opened at `open`, with the `,` of its alternatives.
+fn group(bytes: &[u8], open: usize) -> Option<(usize, Vec<usize>)> {
+    let (left, right) = if bytes[open] == b'{' {
+        (b'{', b'}')
+    } else {
+        (b'(', b')')
+    };
+    let mut depth = 0;
+    let mut commas = Vec::new();
+    let mut i = open;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 1,
+            b if b == left => depth += 1,
+            b if b == right => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some((i, commas));
+                }
+            }
+            b',' if depth == 1 && left == b'{' => commas.push(i),
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Returns `true` if the group follows the name of a parameter and has only literals, as
+/// parsed by [`Parser`], e.g. `:lang(en|fr)`.
+fn is_alternation(bytes: &[u8], open: usize, close: usize) -> bool {
+    let start = bytes[..open]
+        .iter()
+        .rposition(|b| b"-.~/\\:(?+*".contains(b))
+        .map_or(0, |i| i + 1);
+    let named = start > 0 && bytes[start - 1] == b':' && (start < 2 || bytes[start - 2] != b'\\');
+    named && {
+        let mut i = open + 1;
+        let mut literals = true;
+        while i < close {
+            match bytes[i] {
+                b'\\' => i += 1,
+                b'/' | b':' | b'+' | b'*' | b'(' => literals = false,
+                _ => {}
+            }
+            i += 1;
+        }
+        literals
+    }
 }
 
 impl Iterator for Parser<'_> {
@@ -149,6 +353,11 @@ impl Iterator for Parser<'_> {
                     self.cursor.next();
                     self.pos = i + 1;
                     let (position, kind) = self.parameter();
+                    if kind == Kind::Normal {
+                        if let Some(literals) = self.alternation() {
+                            return Some(Piece::Alternation(position, literals));
+                        }
+                    }
                     Some(Piece::Parameter(position, kind))
                 }
                 '+' | '*' => {
@@ -0,0 +1,278 @@
+use alloc::{sync::Arc, vec::Vec};
+use core::str::from_utf8;
+
+use smallvec::SmallVec;
+
+use crate::{
+    node::{Parts, RadixNode},
+    parse, parser, Key, Path, Piece,
+};
+
+/// A persistent path tree, its versions share the unchanged nodes and routes.
+///
+/// [`PersistentPathTree::insert`] and [`PersistentPathTree::remove`] return a new version and
+/// leave the previous one intact, only copying the nodes on the path of the change.
+/// Cloning a version is cheap, so they can be kept around for rollbacks or A/B configs.
+#[derive(Debug)]
+pub struct PersistentPathTree<T> {
+    id: usize,
+    len: usize,
+    optional_groups: bool,
+    /// The nodes hold the routes with the index of their pattern
+    node: Shared<(Arc<Route<T>>, usize)>,
+}
+
+#[derive(Debug)]
+struct Route<T> {
+    id: usize,
+    value: T,
+    /// The patterns expanded from the groups, the first one is the route
+    patterns: Vec<Vec<Piece>>,
+}
+
+impl<T> PersistentPathTree<T> {
+    /// Creates a new [`PersistentPathTree`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            id: 0,
+            len: 0,
+            optional_groups: false,
+            node: Shared::leaf(Key::String(Vec::new())),
+        }
+    }
+
+    /// Expands the optional groups of the patterns inserted from now on, see
+    /// [`PathTree::with_optional_groups`].
+    ///
+    /// [`PathTree::with_optional_groups`]: crate::PathTree::with_optional_groups
+    #[must_use]
+    pub fn with_optional_groups(mut self) -> Self {
+        self.optional_groups = true;
+        self
+    }
+
+    /// Returns a new version with the path-value inserted, see [`PathTree::insert`].
+    ///
+    /// The groups are expanded into the patterns of one route, the ones already inserted by
+    /// other routes are left to them, unless they are expanded from their groups too. The
+    /// pattern of a route, or of one of its expanded patterns, sets the value of the route.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the pattern expands to more than 256 patterns.
+    ///
+    /// [`PathTree::insert`]: crate::PathTree::insert
+    #[must_use]
+    pub fn insert(&self, path: &str, value: T) -> Self {
+        let mut tree = self.clone();
+        let mut expanded = tree.expand(path).into_iter();
+        let first = expanded.next().unwrap_or_default();
+
+        // the patterns of the route are kept, the new ones are added after them
+        let (id, mut patterns, kept) = if let Some((route, index)) = tree.node.get(&first) {
+            let mut patterns = route.patterns.clone();
+            patterns[*index] = first;
+            (route.id, patterns, route.patterns.len())
+        } else {
+            tree.id += 1;
+            (tree.id - 1, alloc::vec![first], 0)
+        };
+        for pieces in expanded {
+            match tree.node.get(&pieces) {
+                Some((route, index)) if *index > 0 => {
+                    if route.id == id {
+                        patterns[*index] = pieces;
+                    } else {
+                        patterns.push(pieces);
+                    }
+                }
+                Some(_) => {}
+                None => patterns.push(pieces),
+            }
+        }
+
+        let route = Arc::new(Route {
+            id,
+            value,
+            patterns,
+        });
+        for (index, pieces) in route.patterns.iter().enumerate() {
+            // every node of the route shares the new value, the kept patterns since removed or
+            // expanded from the groups of other routes are skipped, and of the patterns of the
+            // same shape the route's own one wins, else the last one like in `PathTree`
+            let owned = match tree.node.get(pieces) {
+                Some((other, i)) if Arc::ptr_eq(other, &route) => *i > 0,
+                Some((other, _)) => other.id == id || index >= kept,
+                None => index >= kept,
+            };
+            if !owned {
+                continue;
+            }
+            let node = tree.node.insert_pieces(pieces).parts_mut();
+            if node.value.is_none() {
+                tree.len += 1;
+            }
+            *node.value = Some((Arc::clone(&route), index));
+        }
+        tree
+    }
+
+    /// Returns a new version without the patterns of the route inserted by the path.
+    ///
+    /// The route is the one of the first pattern found, the patterns expanded from the groups
+    /// which belong to other routes are left to them.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the pattern expands to more than 256 patterns.
+    #[must_use]
+    pub fn remove(&self, path: &str) -> Self {
+        let mut tree = self.clone();
+        let patterns = self.expand(path);
+        let Some(id) = patterns
+            .iter()
+            .find_map(|pieces| tree.node.get(pieces))
+            .map(|(route, _)| route.id)
+        else {
+            return tree;
+        };
+
+        for pieces in &patterns {
+            if tree
+                .node
+                .get(pieces)
+                .is_some_and(|(route, _)| route.id == id)
+            {
+                tree.node.remove(pieces);
+                tree.len -= 1;
+            }
+        }
+        tree
+    }
+
+    /// Parses the patterns the groups of the path expand to, see [`PathTree::insert`].
+    ///
+    /// [`PathTree::insert`]: crate::PathTree::insert
+    fn expand(&self, path: &str) -> Vec<Vec<Piece>> {
+        parser::expand(path, self.optional_groups)
+            .iter()
+            .map(|pattern| parse(pattern))
+            .collect()
+    }
+
+    /// Returns the [`Path`] by the given path.
+    #[must_use]
+    pub fn find<'a, 'b>(&'a self, path: &'b str) -> Option<(&'a T, Path<'a, 'b>)> {
+        let bytes = path.as_bytes();
+        let mut ranges = SmallVec::new_const();
+        self.node
+            .find_into(bytes, &mut ranges)
+            .map(|(route, index)| {
+                (
+                    &route.value,
+                    Path {
+                        id: &route.id,
+                        pieces: &route.patterns[*index],
+                        raws: ranges
+                            .into_iter()
+                            .filter_map(|r| from_utf8(&bytes[r]).ok())
+                            .rev()
+                            .collect(),
+                        query: None,
+                        alias: None,
+                    },
+                )
+            })
+    }
+
+    /// Returns the number of patterns, each one expanded from the groups of a route counts.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if there are no patterns.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl<T> Clone for PersistentPathTree<T> {
+    fn clone(&self) -> Self {
+        Self {
+            id: self.id,
+            len: self.len,
+            optional_groups: self.optional_groups,
+            node: self.node.clone(),
+        }
+    }
+}
+
+impl<T> Default for PersistentPathTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A node shared between the versions, copied on write.
+#[derive(Debug)]
+struct Shared<V>(Arc<Inner<V>>);
+
+#[derive(Clone, Debug)]
+struct Inner<V> {
+    key: Key,
+    value: Option<V>,
+    nodes0: Option<Vec<Shared<V>>>,
+    nodes1: Option<Vec<Shared<V>>>,
+}
+
+impl<V> Clone for Shared<V> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<V: Clone> RadixNode for Shared<V> {
+    type Value = V;
+
+    fn leaf(key: Key) -> Self {
+        Self(Arc::new(Inner {
+            key,
+            value: None,
+            nodes0: None,
+            nodes1: None,
+        }))
+    }
+
+    #[inline]
+    fn key(&self) -> &Key {
+        &self.0.key
+    }
+
+    #[inline]
+    fn value(&self) -> Option<&V> {
+        self.0.value.as_ref()
+    }
+
+    #[inline]
+    fn nodes0(&self) -> Option<&[Self]> {
+        self.0.nodes0.as_deref()
+    }
+
+    #[inline]
+    fn nodes1(&self) -> Option<&[Self]> {
+        self.0.nodes1.as_deref()
+    }
+
+    fn parts_mut(&mut self) -> Parts<'_, Self> {
+        let node = Arc::make_mut(&mut self.0);
+        Parts {
+            key: &mut node.key,
+            value: &mut node.value,
+            nodes0: &mut node.nodes0,
+            nodes1: &mut node.nodes1,
+        }
+    }
+}
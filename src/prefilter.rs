@@ -0,0 +1,101 @@
+use alloc::vec::Vec;
+
+use crate::{Kind, Piece};
+
+/// A set of the static prefixes of the routes, rejecting the paths no route can match.
+///
+/// The set is sorted and prefix-free, so a path is accepted if the greatest prefix not after it
+/// is a prefix of it. A route starting with a parameter has an empty prefix and accepts all.
+#[derive(Clone, Debug, Default)]
+pub struct Prefilter {
+    prefixes: Vec<Vec<u8>>,
+}
+
+impl Prefilter {
+    pub(crate) fn new<'a>(routes: impl Iterator<Item = &'a [Piece]>) -> Self {
+        routes.fold(Self::default(), |mut prefilter, pieces| {
+            prefilter.insert(pieces);
+            prefilter
+        })
+    }
+
+    /// Returns `false` if no route can match the path.
+    #[must_use]
+    pub fn accepts(&self, path: &str) -> bool {
+        self.accepts_bytes(path.as_bytes())
+    }
+
+    #[inline]
+    pub(crate) fn accepts_bytes(&self, bytes: &[u8]) -> bool {
+        // only an empty route or a route starting with a parameter matches it
+        if bytes.is_empty() {
+            return true;
+        }
+
+        match self.search(bytes) {
+            Ok(_) => true,
+            Err(0) => false,
+            Err(i) => bytes.starts_with(&self.prefixes[i - 1]),
+        }
+    }
+
+    /// Returns the number of prefixes.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.prefixes.len()
+    }
+
+    /// Returns `true` if there are no prefixes.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.prefixes.is_empty()
+    }
+
+    pub(crate) fn insert(&mut self, pieces: &[Piece]) {
+        if pieces.is_empty() {
+            return;
+        }
+
+        let prefix = prefix(pieces);
+
+        let Err(i) = self.search(&prefix) else {
+            return;
+        };
+        if i > 0 && prefix.starts_with(&self.prefixes[i - 1]) {
+            return;
+        }
+
+        // drops the longer prefixes it covers
+        let n = self.prefixes[i..]
+            .iter()
+            .take_while(|p| p.starts_with(&prefix))
+            .count();
+        self.prefixes.splice(i..i + n, [prefix]);
+    }
+
+    #[inline]
+    fn search(&self, bytes: &[u8]) -> Result<usize, usize> {
+        self.prefixes.binary_search_by(|p| p[..].cmp(bytes))
+    }
+}
+
+/// Returns the bytes before the first parameter.
+fn prefix(pieces: &[Piece]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    for piece in pieces {
+        match piece {
+            Piece::String(s) => bytes.extend_from_slice(s),
+            Piece::Parameter(_, k) => {
+                // the `/` before a segment can be skipped, e.g. `/posts` matches `/posts/:page?`
+                if matches!(k, Kind::OptionalSegment | Kind::ZeroOrMoreSegment)
+                    && bytes.last() == Some(&b'/')
+                {
+                    bytes.pop();
+                }
+                break;
+            }
+            Piece::Alternation(..) => break,
+        }
+    }
+    bytes
+}
@@ -0,0 +1,66 @@
+use alloc::{string::String, vec::Vec};
+
+use crate::{parse, piece_filter, url, PathTree, Piece, UrlError};
+
+/// Rewrites the paths matched by patterns with templates of the captured parameters.
+///
+/// A rule `/old/:org/:repo/:rest*` to `/:org/:repo/tree/:rest*` rewrites
+/// `/old/viz-rs/path-tree/src` to `/viz-rs/path-tree/tree/src`. The parameters are substituted
+/// by name, and the anonymous ones by kind and position, e.g. `/v1/*` to `/v2/*`.
+#[derive(Clone, Debug, Default)]
+pub struct Rewriter {
+    tree: PathTree<Vec<Piece>>,
+}
+
+impl Rewriter {
+    /// Creates a new [`Rewriter`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Inserts a rule rewriting the paths matched by the pattern with the template, and
+    /// returns the id of the pattern.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`UrlError::Unknown`] if the template refers to a parameter the pattern
+    /// does not have.
+    pub fn insert(&mut self, pattern: &str, template: &str) -> Result<usize, UrlError> {
+        let pieces = parse(pattern);
+        let template = parse(template);
+        if let Some(name) = template
+            .iter()
+            .filter_map(piece_filter)
+            .find(|name| !pieces.iter().filter_map(piece_filter).any(|n| n == *name))
+        {
+            return Err(UrlError::Unknown(name.into()));
+        }
+        Ok(self.tree.insert(pattern, template))
+    }
+
+    /// Rewrites the path of the request target, keeping its query.
+    #[must_use]
+    pub fn rewrite(&self, target: &str) -> Option<String> {
+        let (template, path) = self.tree.find_target(target)?;
+        let mut url = url::join(
+            template,
+            |piece| {
+                let name = piece_filter(piece)?;
+                path.params_iter().find(|(k, _)| *k == name).map(|(_, v)| v)
+            },
+            false,
+        );
+        if let Some(query) = path.query {
+            url.push('?');
+            url.push_str(query);
+        }
+        Some(url)
+    }
+
+    /// Returns the tree of the patterns, with the parsed templates.
+    #[must_use]
+    pub fn tree(&self) -> &PathTree<Vec<Piece>> {
+        &self.tree
+    }
+}
@@ -0,0 +1,30 @@
+//! Byte scanning, vectorized by `memchr` when the feature is enabled.
+
+/// Returns the index of the first `needle` in the `haystack`.
+#[inline]
+pub(crate) fn position(needle: u8, haystack: &[u8]) -> Option<usize> {
+    #[cfg(feature = "memchr")]
+    {
+        memchr::memchr(needle, haystack)
+    }
+    #[cfg(not(feature = "memchr"))]
+    {
+        haystack.iter().position(|b| *b == needle)
+    }
+}
+
+/// Returns the indexes of all `needle`s in the `haystack`.
+#[inline]
+pub(crate) fn positions(needle: u8, haystack: &[u8]) -> impl Iterator<Item = usize> + '_ {
+    #[cfg(feature = "memchr")]
+    {
+        memchr::memchr_iter(needle, haystack)
+    }
+    #[cfg(not(feature = "memchr"))]
+    {
+        haystack
+            .iter()
+            .enumerate()
+            .filter_map(move |(n, b)| (*b == needle).then_some(n))
+    }
+}
@@ -0,0 +1,90 @@
+use core::ops::Deref;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use arc_swap::{ArcSwap, Guard};
+
+use crate::PathTree;
+
+/// A [`PathTree`] shared by concurrent readers and swapped by writers.
+///
+/// Readers take a [`Snapshot`] without locking and find in it, while a writer builds a new
+/// version of the tree aside and publishes it atomically. A snapshot keeps its version alive,
+/// so the returned [`Path`](crate::Path)s stay valid across a reload.
+#[derive(Debug)]
+pub struct SharedPathTree<T> {
+    current: ArcSwap<PathTree<T>>,
+    /// Serializes the writers, so concurrent updates are not lost
+    writer: Mutex<()>,
+}
+
+impl<T> SharedPathTree<T> {
+    /// Creates a new [`SharedPathTree`] publishing the tree.
+    #[must_use]
+    pub fn new(tree: PathTree<T>) -> Self {
+        Self {
+            current: ArcSwap::from_pointee(tree),
+            writer: Mutex::new(()),
+        }
+    }
+
+    /// Returns a snapshot of the current version of the tree.
+    ///
+    /// It is cheap and lock-free, meant to be held for a request rather than kept around.
+    #[must_use]
+    pub fn snapshot(&self) -> Snapshot<T> {
+        Snapshot(self.current.load())
+    }
+
+    /// Publishes a new version of the tree, e.g. rebuilt without the removed routes.
+    pub fn store(&self, tree: PathTree<T>) {
+        let _writer = self.writer.lock().unwrap_or_else(PoisonError::into_inner);
+        self.current.store(Arc::new(tree));
+    }
+
+    /// Updates a copy of the current tree and publishes it.
+    ///
+    /// The readers keep finding in the previous version until `f` returns.
+    pub fn update<F, R>(&self, f: F) -> R
+    where
+        T: Clone,
+        F: FnOnce(&mut PathTree<T>) -> R,
+    {
+        let _writer = self.writer.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut tree = PathTree::clone(&self.current.load());
+        let r = f(&mut tree);
+        self.current.store(Arc::new(tree));
+        r
+    }
+}
+
+impl<T> Default for SharedPathTree<T> {
+    fn default() -> Self {
+        Self::new(PathTree::new())
+    }
+}
+
+impl<T> From<PathTree<T>> for SharedPathTree<T> {
+    fn from(tree: PathTree<T>) -> Self {
+        Self::new(tree)
+    }
+}
+
+/// A version of a [`SharedPathTree`], see [`SharedPathTree::snapshot`].
+#[derive(Debug)]
+pub struct Snapshot<T>(Guard<Arc<PathTree<T>>>);
+
+impl<T> Snapshot<T> {
+    /// Returns the version as an [`Arc`], which can be kept around.
+    #[must_use]
+    pub fn into_arc(self) -> Arc<PathTree<T>> {
+        Guard::into_inner(self.0)
+    }
+}
+
+impl<T> Deref for Snapshot<T> {
+    type Target = PathTree<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
@@ -0,0 +1,165 @@
+use alloc::vec::Vec;
+
+use crate::{Node, Piece};
+
+/// An open-addressing hash index of fully static routes.
+///
+/// It is only a shortcut: a path found here is always matched by the same route
+/// when walking the nodes, so it can be consulted before the walk.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct StaticIndex {
+    slots: Vec<Slot>,
+    /// Stores the keys
+    bytes: Vec<u8>,
+    len: usize,
+}
+
+#[derive(Clone, Debug, Default)]
+struct Slot {
+    hash: u64,
+    key: (u32, u32),
+    id: Option<usize>,
+}
+
+impl StaticIndex {
+    /// Indexes the fully static patterns of the tree, by node value.
+    pub(crate) fn new<'a>(
+        node: &Node<usize>,
+        patterns: impl Iterator<Item = (usize, &'a [Piece])>,
+    ) -> Self {
+        let mut statics = Self::default();
+        for (id, pieces) in patterns {
+            if let Some(bytes) = static_bytes(pieces) {
+                // only when the walk reaches them
+                if node.find(&bytes).is_some_and(|(i, _)| *i == id) {
+                    statics.insert(&bytes, id);
+                }
+            }
+        }
+        statics
+    }
+
+    #[inline]
+    pub(crate) fn get(&self, bytes: &[u8]) -> Option<&usize> {
+        if self.len == 0 {
+            return None;
+        }
+
+        let h = hash(bytes);
+        let mask = self.slots.len() - 1;
+        let mut i = index(h, mask);
+        loop {
+            let slot = &self.slots[i];
+            match &slot.id {
+                Some(id) if slot.hash == h && self.key(slot) == bytes => return Some(id),
+                Some(_) => i = (i + 1) & mask,
+                None => return None,
+            }
+        }
+    }
+
+    pub(crate) fn insert(&mut self, bytes: &[u8], id: usize) {
+        // keeps the load factor under 1/2
+        if (self.len + 1) * 2 > self.slots.len() {
+            self.grow();
+        }
+
+        let h = hash(bytes);
+        let mask = self.slots.len() - 1;
+        let mut i = index(h, mask);
+        loop {
+            let slot = &self.slots[i];
+            match slot.id {
+                Some(_) if slot.hash == h && self.key(slot) == bytes => {
+                    self.slots[i].id = Some(id);
+                    return;
+                }
+                Some(_) => i = (i + 1) & mask,
+                None => {
+                    let start = self.bytes.len();
+                    self.bytes.extend_from_slice(bytes);
+                    self.slots[i] = Slot {
+                        hash: h,
+                        key: (to_u32(start), to_u32(bytes.len())),
+                        id: Some(id),
+                    };
+                    self.len += 1;
+                    return;
+                }
+            }
+        }
+    }
+
+    #[inline]
+    fn key(&self, slot: &Slot) -> &[u8] {
+        let (start, len) = slot.key;
+        &self.bytes[start as usize..(start + len) as usize]
+    }
+
+    fn grow(&mut self) {
+        let capacity = (self.slots.len() * 2).max(16);
+        let slots = core::mem::replace(&mut self.slots, {
+            let mut slots = Vec::with_capacity(capacity);
+            slots.resize_with(capacity, Slot::default);
+            slots
+        });
+        let mask = capacity - 1;
+        for slot in slots.into_iter().filter(|slot| slot.id.is_some()) {
+            let mut i = index(slot.hash, mask);
+            while self.slots[i].id.is_some() {
+                i = (i + 1) & mask;
+            }
+            self.slots[i] = slot;
+        }
+    }
+}
+
+#[allow(clippy::cast_possible_truncation)]
+#[inline]
+fn index(hash: u64, mask: usize) -> usize {
+    hash as usize & mask
+}
+
+/// Hashes a word at a time, like `FxHash`.
+#[inline]
+fn hash(bytes: &[u8]) -> u64 {
+    const K: u64 = 0x517c_c1b7_2722_0a95;
+
+    #[inline]
+    fn add(h: u64, word: u64) -> u64 {
+        (h.rotate_left(5) ^ word).wrapping_mul(K)
+    }
+
+    let mut chunks = bytes.chunks_exact(8);
+    let mut h = chunks.by_ref().fold(bytes.len() as u64, |h, chunk| {
+        add(h, u64::from_le_bytes(chunk.try_into().unwrap()))
+    });
+    let rest = chunks.remainder();
+    if !rest.is_empty() {
+        let mut word = [0; 8];
+        word[..rest.len()].copy_from_slice(rest);
+        h = add(h, u64::from_le_bytes(word));
+    }
+    h
+}
+
+#[inline]
+fn to_u32(n: usize) -> u32 {
+    u32::try_from(n).expect("static index exceeds u32::MAX")
+}
+
+/// Returns the bytes of a route made of string pieces only.
+pub(crate) fn static_bytes(pieces: &[Piece]) -> Option<Vec<u8>> {
+    if pieces.is_empty() {
+        return None;
+    }
+
+    let mut bytes = Vec::new();
+    for piece in pieces {
+        match piece {
+            Piece::String(s) => bytes.extend_from_slice(s),
+            Piece::Parameter(..) | Piece::Alternation(..) => return None,
+        }
+    }
+    Some(bytes)
+}
@@ -0,0 +1,282 @@
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt;
+
+use crate::{piece_filter, Kind, PathTree, Piece};
+
+/// An error of [`PathTree::try_url_for`] and [`Rewriter::insert`].
+///
+/// [`Rewriter::insert`]: crate::Rewriter::insert
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum UrlError {
+    /// No route has the id.
+    NotFound,
+    /// A required parameter is not given.
+    Missing(String),
+    /// More params are given than the route has parameters, by the number of them.
+    Extra(usize),
+    /// An empty value is given to a parameter which matches one byte at least.
+    Empty(String),
+    /// A value with `/` is given to a parameter which matches within a segment.
+    Separator(String),
+    /// A value not among the literals is given to an alternation.
+    Unlisted(String),
+    /// The template of a [`Rewriter`] rule refers to a parameter the pattern does not have.
+    ///
+    /// [`Rewriter`]: crate::Rewriter
+    Unknown(String),
+}
+
+impl fmt::Display for UrlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound => f.write_str("route not found"),
+            Self::Missing(name) => write!(f, "missing parameter `{name}`"),
+            Self::Extra(n) => write!(f, "{n} extra parameters"),
+            Self::Empty(name) => write!(f, "empty value of parameter `{name}`"),
+            Self::Separator(name) => write!(f, "separator in value of parameter `{name}`"),
+            Self::Unlisted(name) => write!(f, "unlisted value of parameter `{name}`"),
+            Self::Unknown(name) => write!(f, "unknown parameter `{name}`"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for UrlError {}
+
+impl<T> PathTree<T> {
+    /// Generates URL with the params, checked against the parameters of the route.
+    ///
+    /// The optional parameters can be left out at the end, or skipped with an empty value.
+    /// The values are percent-encoded, with `/` kept for `+` and `*`. The optional groups are
+    /// left out like in [`PathTree::url_for`].
+    ///
+    /// # Errors
+    ///
+    /// Will return [`UrlError::NotFound`] if no route has the id, [`UrlError::Missing`] or
+    /// [`UrlError::Extra`] if fewer or more params are given than the route has parameters,
+    /// and [`UrlError::Empty`], [`UrlError::Separator`] or [`UrlError::Unlisted`] if a value
+    /// does not fit its parameter.
+    pub fn try_url_for(&self, index: usize, params: &[&str]) -> Result<String, UrlError> {
+        let pieces = self
+            .variant(index, |pieces| arity(pieces) == given(params))
+            .ok_or(UrlError::NotFound)?;
+        let mut values = Vec::with_capacity(params.len());
+        let mut iter = params.iter();
+
+        for piece in pieces {
+            if let Piece::Alternation(_, literals) = piece {
+                let name = || piece_filter(piece).unwrap_or_default().to_string();
+                let value = *iter.next().ok_or_else(|| UrlError::Missing(name()))?;
+                if !literals.iter().any(|literal| literal == value.as_bytes()) {
+                    return Err(UrlError::Unlisted(name()));
+                }
+                values.push(value);
+            } else if let Piece::Parameter(_, kind) = piece {
+                let name = || piece_filter(piece).unwrap_or_default().to_string();
+                let required = matches!(kind, Kind::Normal | Kind::OneOrMore);
+                let value = match iter.next() {
+                    Some(value) => value,
+                    None if required => return Err(UrlError::Missing(name())),
+                    None => "",
+                };
+                if value.is_empty() && required {
+                    return Err(UrlError::Empty(name()));
+                }
+
+                let segments = matches!(
+                    kind,
+                    Kind::OneOrMore | Kind::ZeroOrMore | Kind::ZeroOrMoreSegment
+                );
+                if !segments && value.contains('/') {
+                    return Err(UrlError::Separator(name()));
+                }
+                values.push(value);
+            }
+        }
+
+        match iter.len() {
+            0 => {
+                let mut values = values.into_iter();
+                Ok(join(pieces, |_| values.next(), true))
+            }
+            n => Err(UrlError::Extra(n)),
+        }
+    }
+}
+
+impl<T> PathTree<T> {
+    /// Generates URL with the params and the query, see [`PathTree::url_for`].
+    ///
+    /// The query pairs are percent-encoded, and left out with the `?` if empty.
+    #[must_use]
+    pub fn url_for_query(
+        &self,
+        index: usize,
+        params: &[&str],
+        query: &[(&str, &str)],
+    ) -> Option<String> {
+        self.url_for(index, params).map(|mut url| {
+            push_query(&mut url, query);
+            url
+        })
+    }
+
+    /// Generates URL with the params and the query, see [`PathTree::try_url_for`].
+    ///
+    /// # Errors
+    ///
+    /// Will return the errors of [`PathTree::try_url_for`], the query is not checked.
+    pub fn try_url_for_query(
+        &self,
+        index: usize,
+        params: &[&str],
+        query: &[(&str, &str)],
+    ) -> Result<String, UrlError> {
+        self.try_url_for(index, params).map(|mut url| {
+            push_query(&mut url, query);
+            url
+        })
+    }
+}
+
+/// Returns the number of parameters of the pattern.
+pub(crate) fn arity(pieces: &[Piece]) -> usize {
+    pieces
+        .iter()
+        .filter(|piece| !matches!(piece, Piece::String(_)))
+        .count()
+}
+
+/// Returns the number of params given, without the empty ones at the end.
+pub(crate) fn given(params: &[&str]) -> usize {
+    params
+        .iter()
+        .rposition(|param| !param.is_empty())
+        .map_or(0, |i| i + 1)
+}
+
+/// Splits the request target into the path and the raw query, dropping the fragment.
+pub(crate) fn split_target(target: &str) -> (&str, Option<&str>) {
+    let target = target.split_once('#').map_or(target, |(target, _)| target);
+    match target.split_once('?') {
+        Some((path, query)) => (path, Some(query)),
+        None => (target, None),
+    }
+}
+
+/// Appends the query pairs to the URL.
+fn push_query(url: &mut String, query: &[(&str, &str)]) {
+    for (i, (key, value)) in query.iter().enumerate() {
+        url.push(if i == 0 { '?' } else { '&' });
+        encode(url, key, is_unreserved);
+        url.push('=');
+        encode(url, value, is_unreserved);
+    }
+}
+
+/// Joins the pieces with the values of the parameters, the missing ones are left out.
+///
+/// The segment parameters left out at the end drop their `/` too, e.g. `/posts` for
+/// `/posts/:page?`, while the ones in the middle leave an empty segment, e.g. `/a//b` for
+/// `/a/:x?/b`, so the URL is matched by the route with the same values.
+pub(crate) fn join<'p, F>(pieces: &[Piece], mut value: F, encoded: bool) -> String
+where
+    F: FnMut(&Piece) -> Option<&'p str>,
+{
+    let values = pieces
+        .iter()
+        .filter(|piece| !matches!(piece, Piece::String(_)))
+        .map(|piece| value(piece).unwrap_or_default())
+        .collect::<Vec<_>>();
+
+    // the pieces kept, the last string without its `/` if `strip`
+    let mut end = pieces.len();
+    let mut strip = false;
+    let mut i = values.len();
+    while let [.., Piece::String(s), Piece::Parameter(_, kind)] = &pieces[..end] {
+        let segment = matches!(kind, Kind::OptionalSegment | Kind::ZeroOrMoreSegment);
+        if !segment || !values[i - 1].is_empty() || s.last() != Some(&b'/') {
+            break;
+        }
+        i -= 1;
+        if s.len() > 1 {
+            end -= 1;
+            strip = true;
+            break;
+        }
+        end -= 2;
+    }
+
+    let mut url = String::new();
+    let mut values = values.into_iter();
+    for (i, piece) in pieces[..end].iter().enumerate() {
+        match piece {
+            Piece::String(s) => {
+                let s = if strip && i + 1 == end {
+                    &s[..s.len() - 1]
+                } else {
+                    s
+                };
+                // the pattern is only split at ASCII bytes
+                url.push_str(&String::from_utf8_lossy(s));
+            }
+            Piece::Parameter(..) | Piece::Alternation(..) => {
+                let value = values.next().unwrap_or_default();
+                if encoded {
+                    encode(&mut url, value, is_pchar);
+                } else {
+                    url.push_str(value);
+                }
+            }
+        }
+    }
+
+    if url.is_empty() && !pieces.is_empty() {
+        url.push('/');
+    }
+    url
+}
+
+/// Percent-encodes the bytes of the value which are not kept.
+fn encode(url: &mut String, value: &str, keep: fn(u8) -> bool) {
+    const HEX: &[u8; 16] = b"0123456789ABCDEF";
+
+    for &b in value.as_bytes() {
+        if keep(b) {
+            url.push(char::from(b));
+        } else {
+            url.push('%');
+            url.push(char::from(HEX[usize::from(b >> 4)]));
+            url.push(char::from(HEX[usize::from(b & 15)]));
+        }
+    }
+}
+
+/// The unreserved bytes of RFC 3986.
+fn is_unreserved(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || matches!(b, b'-' | b'.' | b'_' | b'~')
+}
+
+/// The `pchar` bytes of RFC 3986 and `/`.
+fn is_pchar(b: u8) -> bool {
+    is_unreserved(b)
+        || matches!(
+            b,
+            b'!' | b'$'
+                | b'&'
+                | b'\''
+                | b'('
+                | b')'
+                | b'*'
+                | b'+'
+                | b','
+                | b';'
+                | b'='
+                | b':'
+                | b'@'
+                | b'/'
+        )
+}
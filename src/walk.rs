@@ -0,0 +1,527 @@
+use alloc::vec::Vec;
+use core::{ops::Range, slice::Iter};
+
+use smallvec::SmallVec;
+
+use crate::{
+    node::{compare, RadixNode},
+    scan::{position, positions},
+    Key, Kind,
+};
+
+/// The key of a node borrowed for the walk.
+#[derive(Clone, Copy)]
+pub(crate) enum KeyRef<'a> {
+    String(&'a [u8]),
+    Parameter(Kind),
+    /// The literals are read with [`Walk::literal`]
+    Alternation,
+}
+
+/// A node borrowed for the walk, so the nodes and the flat layout of a
+/// [`FrozenPathTree`](crate::FrozenPathTree) are matched by the same [`find_with`].
+pub(crate) trait Walk<'a>: Copy {
+    type Value: 'a;
+    type Nodes: DoubleEndedIterator<Item = Self>;
+
+    fn key(self) -> KeyRef<'a>;
+
+    /// Returns the literal of the alternation by index.
+    fn literal(self, index: usize) -> Option<&'a [u8]>;
+
+    fn value(self) -> Option<&'a Self::Value>;
+
+    fn nodes0(self) -> Option<Self::Nodes>;
+
+    fn nodes1(self) -> Option<Self::Nodes>;
+
+    /// Returns the string node starting with the first of the bytes.
+    ///
+    /// The byte is read from the bytes in the comparisons, a copy of it made the search of the
+    /// nodes about a third slower.
+    fn child(self, bytes: &[u8]) -> Option<Self>;
+
+    /// Returns the minimum length of the bytes matched by the node and a route below it, `0`
+    /// if it is not known.
+    #[inline]
+    fn min(self) -> usize {
+        0
+    }
+
+    #[inline]
+    fn is_leaf(self) -> bool {
+        self.nodes0().is_none() && self.nodes1().is_none()
+    }
+
+    #[inline]
+    fn first_byte(self) -> u8 {
+        match self.key() {
+            KeyRef::String(s) => s[0],
+            KeyRef::Parameter(_) | KeyRef::Alternation => unreachable!(),
+        }
+    }
+}
+
+impl<'a, N: RadixNode> Walk<'a> for &'a N {
+    type Value = N::Value;
+    type Nodes = Iter<'a, N>;
+
+    #[inline]
+    fn key(self) -> KeyRef<'a> {
+        match RadixNode::key(self) {
+            Key::String(s) => KeyRef::String(s),
+            Key::Parameter(k) => KeyRef::Parameter(*k),
+            Key::Alternation(_) => KeyRef::Alternation,
+        }
+    }
+
+    #[inline]
+    fn literal(self, index: usize) -> Option<&'a [u8]> {
+        match RadixNode::key(self) {
+            Key::Alternation(literals) => literals.get(index).map(|literal| &literal[..]),
+            Key::String(_) | Key::Parameter(_) => None,
+        }
+    }
+
+    #[inline]
+    fn value(self) -> Option<&'a N::Value> {
+        RadixNode::value(self)
+    }
+
+    #[inline]
+    fn nodes0(self) -> Option<Self::Nodes> {
+        RadixNode::nodes0(self).map(<[N]>::iter)
+    }
+
+    #[inline]
+    fn nodes1(self) -> Option<Self::Nodes> {
+        RadixNode::nodes1(self).map(<[N]>::iter)
+    }
+
+    #[inline]
+    fn child(self, bytes: &[u8]) -> Option<Self> {
+        let nodes = RadixNode::nodes0(self)?;
+        nodes
+            .binary_search_by(|node| match RadixNode::key(node) {
+                // lets `/` at end
+                Key::String(s) => compare(s[0], bytes[0]),
+                Key::Parameter(_) | Key::Alternation(_) => unreachable!(),
+            })
+            .ok()
+            .map(|i| &nodes[i])
+    }
+}
+
+/// Finds the bytes below the node, pushing the ranges of the parameters from the last one.
+#[allow(clippy::range_plus_one)]
+#[allow(clippy::too_many_lines)]
+#[inline]
+pub(crate) fn find_with<'a, N: Walk<'a>>(
+    node: N,
+    start: usize,
+    mut bytes: &[u8],
+    ranges: &mut SmallVec<[Range<usize>; 8]>,
+) -> Option<&'a N::Value> {
+    let m = bytes.len();
+
+    match node.key() {
+        KeyRef::String(s) => {
+            let n = s.len();
+            let mut flag = m >= n;
+
+            // opt!
+            if flag {
+                if n == 1 {
+                    flag = s[0] == bytes[0];
+                } else {
+                    flag = s == &bytes[..n];
+                }
+            }
+
+            // starts with prefix
+            if flag {
+                return find_below(node, start + n, &bytes[n..], ranges);
+            } else if n == 1 && s[0] == b'/' || m + 1 == n && s[m] == b'/' && s[..m] == *bytes {
+                // skips `/` to the segment parameters, e.g. `/posts` for `/posts/:page?`
+                let (start, bytes) = if n == 1 {
+                    (start, bytes)
+                } else {
+                    (start + m, &bytes[m..])
+                };
+                if let Some(id) = node.nodes1().and_then(|nodes| {
+                    nodes
+                        .filter(|node| {
+                            matches!(node.key(),
+                                KeyRef::Parameter(pk)
+                                    if pk == Kind::OptionalSegment
+                                        || pk == Kind::ZeroOrMoreSegment
+                            )
+                        })
+                        .find_map(|node| find_with(node, start, bytes, ranges))
+                }) {
+                    return Some(id);
+                }
+            }
+        }
+        KeyRef::Alternation => return find_alternation(node, start, bytes, ranges),
+        KeyRef::Parameter(k) => match k {
+            Kind::Normal | Kind::Optional | Kind::OptionalSegment => {
+                if m == 0 {
+                    if k == Kind::Normal {
+                        return None;
+                    }
+
+                    // last
+                    if node.is_leaf() {
+                        return node.value().inspect(|_| {
+                            ranges.push(start..start);
+                        });
+                    }
+                } else {
+                    // static
+                    if let Some(id) = node.nodes0().and_then(|mut nodes| {
+                        // the piece ends at the first `/`, inclusive
+                        let end = position(b'/', bytes).map_or(m, |n| n + 1);
+                        nodes.find_map(|node| {
+                            positions(node.first_byte(), &bytes[..end]).find_map(|n| {
+                                find_with(node, start + n, &bytes[n..], ranges).inspect(|_| {
+                                    ranges.push(start..start + n);
+                                })
+                            })
+                        })
+                    }) {
+                        return Some(id);
+                    }
+
+                    // parameter => `:a:b:c`
+                    if let Some(id) = node.nodes1().and_then(|nodes| {
+                        let b = m - 1 > 0;
+                        nodes
+                            .filter(|node| match node.key() {
+                                KeyRef::Parameter(pk)
+                                    if pk == Kind::Normal || pk == Kind::OneOrMore =>
+                                {
+                                    b
+                                }
+                                _ => true,
+                            })
+                            .find_map(|node| find_with(node, start + 1, &bytes[1..], ranges))
+                    }) {
+                        ranges.push(start..start + 1);
+                        return Some(id);
+                    }
+                }
+
+                // parameter => `:a:b?:c?`
+                if k == Kind::Optional || k == Kind::OptionalSegment {
+                    if let Some(id) = node.nodes1().and_then(|nodes| {
+                        let b = m > 0;
+                        nodes
+                            .filter(|node| match node.key() {
+                                KeyRef::Parameter(pk)
+                                    if pk == Kind::Normal || pk == Kind::OneOrMore =>
+                                {
+                                    b
+                                }
+                                _ => true,
+                            })
+                            .find_map(|node| find_with(node, start, bytes, ranges))
+                    }) {
+                        // param should be empty
+                        ranges.push(start + m..start + m);
+                        return Some(id);
+                    }
+                }
+
+                if let Some(n) = position(b'/', bytes) {
+                    bytes = &bytes[n..];
+                } else {
+                    if let Some(id) = node.value() {
+                        ranges.push(start..start + m);
+                        return Some(id);
+                    }
+                    bytes = &bytes[m..];
+
+                    // the segment parameters after it are left out, e.g. `/api/1` for
+                    // `/api/:day/:month?`
+                    if k == Kind::Normal {
+                        if let Some(id) = node
+                            .nodes0()
+                            .and_then(|mut nodes| nodes.next_back())
+                            .and_then(|node| find_with(node, start + m, bytes, ranges))
+                        {
+                            ranges.push(start..start + m);
+                            return Some(id);
+                        }
+                    }
+                }
+
+                if k == Kind::OptionalSegment {
+                    if let Some(id) = node
+                        .nodes0()
+                        .and_then(|mut nodes| nodes.next_back())
+                        .filter(|node| node.first_byte() == b'/')
+                        .and_then(|node| find_with(node, start, bytes, ranges))
+                    {
+                        ranges.push(start..start + m);
+                        return Some(id);
+                    }
+                }
+            }
+            Kind::OneOrMore | Kind::ZeroOrMore | Kind::ZeroOrMoreSegment => {
+                let is_one_or_more = k == Kind::OneOrMore;
+                if m == 0 {
+                    if is_one_or_more {
+                        return None;
+                    }
+
+                    if node.is_leaf() {
+                        return node.value().inspect(|_| {
+                            ranges.push(start..start);
+                        });
+                    }
+                } else {
+                    if node.is_leaf() {
+                        if let Some(id) = node.value() {
+                            ranges.push(start..start + m);
+                            return Some(id);
+                        }
+                    }
+
+                    // static
+                    if let Some(id) = node.nodes0().and_then(|mut nodes| {
+                        nodes.find_map(|node| {
+                            if let KeyRef::String(s) = node.key() {
+                                let right_length = if is_one_or_more {
+                                    m > s.len()
+                                } else {
+                                    m >= s.len()
+                                };
+                                if right_length && node.min() <= m {
+                                    // leaves enough bytes for the rest of the route
+                                    return positions(s[0], &bytes[..(m + 1 - node.min()).min(m)])
+                                        // `+` takes one byte at least
+                                        .filter(|&n| n > 0 || !is_one_or_more)
+                                        .find_map(|n| {
+                                            find_with(node, start + n, &bytes[n..], ranges).inspect(
+                                                |_| {
+                                                    ranges.push(start..start + n);
+                                                },
+                                            )
+                                        });
+                                }
+                            }
+                            None
+                        })
+                    }) {
+                        return Some(id);
+                    }
+                }
+
+                if k == Kind::ZeroOrMoreSegment {
+                    if let Some(id) = node
+                        .nodes0()
+                        .and_then(|mut nodes| nodes.next_back())
+                        .filter(|node| node.first_byte() == b'/')
+                        .and_then(|node| find_with(node, start, bytes, ranges))
+                    {
+                        // param should be empty
+                        ranges.push(start + m..start + m);
+                        return Some(id);
+                    }
+                }
+
+                if m > 0 {
+                    // the segment parameters after it are left out
+                    if let Some(id) = node
+                        .nodes0()
+                        .and_then(|mut nodes| nodes.next_back())
+                        .and_then(|node| find_with(node, start + m, &bytes[m..], ranges))
+                    {
+                        ranges.push(start..start + m);
+                        return Some(id);
+                    }
+                }
+            }
+        },
+    }
+    None
+}
+
+/// Finds the bytes after the key of a string node, in its value or children.
+#[inline]
+fn find_below<'a, N: Walk<'a>>(
+    node: N,
+    start: usize,
+    bytes: &[u8],
+    ranges: &mut SmallVec<[Range<usize>; 8]>,
+) -> Option<&'a N::Value> {
+    if bytes.is_empty() {
+        if let Some(id) = node.value() {
+            return Some(id);
+        }
+    } else {
+        // static
+        if let Some(id) = node
+            .child(bytes)
+            .and_then(|node| find_with(node, start, bytes, ranges))
+        {
+            return Some(id);
+        }
+    }
+
+    find_parameters(node, start, bytes, ranges)
+}
+
+/// Finds the bytes after the key of a string node, in its parameter children.
+#[inline]
+fn find_parameters<'a, N: Walk<'a>>(
+    node: N,
+    start: usize,
+    bytes: &[u8],
+    ranges: &mut SmallVec<[Range<usize>; 8]>,
+) -> Option<&'a N::Value> {
+    node.nodes1().and_then(|nodes| {
+        let b = !bytes.is_empty();
+        nodes
+            .filter(|node| match node.key() {
+                KeyRef::Parameter(pk) if pk == Kind::Normal || pk == Kind::OneOrMore => b,
+                _ => true,
+            })
+            .find_map(|node| find_with(node, start, bytes, ranges))
+    })
+}
+
+/// Finds the bytes starting with a literal of the alternation.
+///
+/// Kept out of [`find_with`], the walk of the routes without alternations is faster.
+#[cold]
+#[inline(never)]
+fn find_alternation<'a, N: Walk<'a>>(
+    node: N,
+    start: usize,
+    bytes: &[u8],
+    ranges: &mut SmallVec<[Range<usize>; 8]>,
+) -> Option<&'a N::Value> {
+    for literal in (0..)
+        .map_while(|i| node.literal(i))
+        .filter(|literal| bytes.starts_with(literal))
+    {
+        let n = literal.len();
+        if let Some(id) = find_rest(node, start + n, &bytes[n..], ranges) {
+            ranges.push(start..start + n);
+            return Some(id);
+        }
+    }
+    None
+}
+
+/// Finds the rest of the bytes after an alternation, in the value or the children.
+#[inline]
+pub(crate) fn find_rest<'a, N: Walk<'a>>(
+    node: N,
+    start: usize,
+    bytes: &[u8],
+    ranges: &mut SmallVec<[Range<usize>; 8]>,
+) -> Option<&'a N::Value> {
+    if bytes.is_empty() {
+        if let Some(id) = node.value() {
+            return Some(id);
+        }
+        // the segment parameters after it are left out
+        return node
+            .nodes0()
+            .and_then(|mut nodes| nodes.next_back())
+            .and_then(|node| find_with(node, start, bytes, ranges));
+    }
+
+    // static
+    if let Some(id) = node
+        .child(bytes)
+        .and_then(|node| find_with(node, start, bytes, ranges))
+    {
+        return Some(id);
+    }
+
+    // parameter
+    node.nodes1()
+        .and_then(|mut nodes| nodes.find_map(|node| find_with(node, start, bytes, ranges)))
+}
+
+/// Finds the paths of a batch one by one, keeping the string nodes matched from the root by
+/// the previous path so the next one only walks the nodes after the prefix they share.
+pub(crate) struct Descent<'p, N> {
+    root: N,
+    /// The string nodes matched in turn, with the range of the bytes each key matched
+    chain: Vec<(N, Range<usize>)>,
+    previous: &'p [u8],
+}
+
+impl<'a, 'p, N: Walk<'a>> Descent<'p, N> {
+    pub(crate) fn new(root: N) -> Self {
+        Self {
+            root,
+            chain: Vec::new(),
+            previous: &[],
+        }
+    }
+
+    /// Finds the bytes like [`find_with`] from the root.
+    pub(crate) fn find(
+        &mut self,
+        bytes: &'p [u8],
+        ranges: &mut SmallVec<[Range<usize>; 8]>,
+    ) -> Option<&'a N::Value> {
+        let shared = self
+            .previous
+            .iter()
+            .zip(bytes)
+            .take_while(|(a, b)| a == b)
+            .count();
+        self.previous = bytes;
+
+        // keeps the nodes matched within the shared bytes
+        let keep = self
+            .chain
+            .iter()
+            .take_while(|(_, range)| range.end <= shared)
+            .count();
+        self.chain.truncate(keep);
+
+        if self.chain.is_empty() {
+            match self.root.key() {
+                KeyRef::String(s) if bytes.starts_with(s) => {
+                    self.chain.push((self.root, 0..s.len()));
+                }
+                _ => return find_with(self.root, 0, bytes, ranges),
+            }
+        }
+
+        // descends the string nodes whose keys match in full
+        while let Some(&(node, Range { end, .. })) = self.chain.last() {
+            let Some(child) = bytes
+                .get(end..)
+                .filter(|rest| !rest.is_empty())
+                .and_then(|rest| node.child(rest))
+            else {
+                break;
+            };
+            match child.key() {
+                KeyRef::String(s) if bytes[end..].starts_with(s) => {
+                    self.chain.push((child, end..end + s.len()));
+                }
+                _ => break,
+            }
+        }
+
+        let ((node, range), above) = self.chain.split_last()?;
+        if let Some(id) = find_below(*node, range.end, &bytes[range.end..], ranges) {
+            return Some(id);
+        }
+
+        // backtracks to the parameters of the nodes above, like the walk from the root
+        above.iter().rev().find_map(|(node, range)| {
+            find_parameters(*node, range.end, &bytes[range.end..], ranges)
+        })
+    }
+}
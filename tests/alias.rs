@@ -0,0 +1,143 @@
+#![allow(unused_must_use)]
+
+use path_tree::{Captures, PathTree};
+
+#[test]
+fn aliases() {
+    let mut tree = PathTree::new();
+    let users = tree.insert("/users/:id", "users");
+    let profile = tree.insert("/settings/profile", "profile");
+    tree.insert("/users/:id/posts", "posts");
+
+    assert_eq!(tree.insert_alias("/u/:uid", users), Some(0));
+    assert_eq!(tree.insert_alias("/me", profile), Some(1));
+    assert_eq!(tree.insert_alias("/people/:id", users), Some(2));
+    assert_eq!(tree.get_alias(0).map(|(id, _)| id), Some(users));
+    assert_eq!(tree.get_alias(3), None);
+
+    // the route itself, unknown ids and groups are rejected
+    assert_eq!(tree.insert_alias("/users/:name", profile), None);
+    assert_eq!(tree.insert_alias("/x", 9), None);
+    assert!(tree.find("/x").is_none());
+    assert_eq!(tree.insert_alias("/{x,y}", profile), None);
+    assert!(tree.find("/x").is_none());
+    assert!(tree.find("/{x,y}").is_none());
+
+    let (h, p) = tree.find("/u/1").unwrap();
+    assert_eq!((*h, *p.id, p.alias), ("users", users, Some(0)));
+    assert_eq!(p.params(), vec![("uid", "1")]);
+    assert_eq!(p.pattern(), "/u/:uid");
+    assert_eq!(p.url_with(&[("uid", "2")]), "/u/2");
+
+    let (h, p) = tree.find("/users/1").unwrap();
+    assert_eq!((*h, p.alias), ("users", None));
+    assert_eq!(p.params(), vec![("id", "1")]);
+
+    let (h, p) = tree.find("/me").unwrap();
+    assert_eq!((*h, *p.id, p.alias), ("profile", profile, Some(1)));
+    // the nodes of the aliases hold tagged values
+    assert_eq!(tree.node.find(b"/users/1").map(|(id, _)| *id), Some(users));
+    assert_eq!(
+        tree.node.find(b"/me").map(|(id, _)| *id),
+        Some(usize::MAX / 2 + 2)
+    );
+    assert_eq!(
+        tree.find_target("/people/2?tab=repos").unwrap().1.alias,
+        Some(2)
+    );
+    assert!(tree.find("/u/1/posts").is_none());
+
+    // the canonical pattern is generated
+    assert_eq!(tree.url_for(users, &["1"]).unwrap(), "/users/1");
+
+    assert_eq!(tree.id_of("/u/:x"), Some(users));
+    assert_eq!(tree.get_by_pattern("/me"), Some((profile, &"profile")));
+    assert_eq!(tree.entry("/people/:id").id(), Some(users));
+
+    let mut captures = Captures::new();
+    let (h, pieces) = tree.find_into("/u/3", &mut captures).unwrap();
+    assert_eq!(*h, "users");
+    assert_eq!(captures.id(), users);
+    assert_eq!(
+        captures.params(pieces, "/u/3").collect::<Vec<_>>(),
+        [("uid", "3")]
+    );
+
+    assert_eq!(
+        tree.classify(["/u/1", "/me", "/users/1", "/nope"]),
+        [Some(users), Some(profile), Some(users), None]
+    );
+
+    let (h, p) = tree.find_mut("/u/4").unwrap();
+    assert_eq!(p.alias, Some(0));
+    *h = "people";
+    assert_eq!(tree.find("/users/4").unwrap().0, &"people");
+
+    // inserting the pattern of an alias sets the value of its route
+    assert_eq!(tree.insert("/u/:id", "users"), users);
+    let (h, p) = tree.find("/u/5").unwrap();
+    assert_eq!((*h, p.alias), ("users", Some(0)));
+    assert_eq!(p.params(), vec![("id", "5")]);
+
+    // an alias inserted again resolves to the new route
+    assert_eq!(tree.insert_alias("/me", users), Some(1));
+    assert_eq!(tree.find("/me").unwrap().0, &"users");
+
+    let frozen = tree.clone().with_prefilter().freeze();
+    for path in [
+        "/u/1",
+        "/me",
+        "/people/2",
+        "/users/3",
+        "/x",
+        "/settings/profile",
+    ] {
+        assert_eq!(frozen.find(path), tree.find(path), "{path}");
+    }
+}
+
+#[test]
+fn retain_aliases() {
+    let mut tree = PathTree::new().with_prefilter();
+    let users = tree.insert("/users/:id", 0);
+    let repos = tree.insert("/repos/:id", 1);
+    let posts = tree.insert("/posts/:id", 2);
+    tree.insert_alias("/r/:id", repos);
+    tree.insert_alias("/u/:id", users);
+    tree.insert_alias("/p/:id", posts);
+    tree.insert_alias("/about", posts);
+
+    tree.retain(|id, _, _| id != repos);
+
+    assert!(tree.find("/r/1").is_none());
+    assert!(tree.find("/repos/1").is_none());
+
+    let (h, p) = tree.find("/u/1").unwrap();
+    assert_eq!((*h, *p.id, p.alias), (0, 0, Some(0)));
+    let (h, p) = tree.find("/p/1").unwrap();
+    assert_eq!((*h, *p.id, p.alias), (2, 1, Some(1)));
+    let (h, p) = tree.find("/about").unwrap();
+    assert_eq!((*h, *p.id, p.alias), (2, 1, Some(2)));
+    assert_eq!(tree.get_alias(2).map(|(id, _)| id), Some(1));
+    assert_eq!(tree.get_alias(3), None);
+
+    // the retained aliases are still kept out of the routes
+    assert_eq!(tree.url_for(1, &["1"]).unwrap(), "/posts/1");
+    assert_eq!(tree.iter().count(), 2);
+}
+
+#[test]
+fn grouped_aliases() {
+    let mut tree = PathTree::new();
+    let wiki = tree.insert("/wiki/:page", 0);
+
+    // the parentheses are literal unless the optional groups are enabled
+    assert_eq!(tree.insert_alias("/w/Rust_(language)", wiki), Some(0));
+    assert_eq!(tree.find("/w/Rust_(language)").unwrap().1.alias, Some(0));
+
+    let mut tree = tree.with_optional_groups();
+    assert_eq!(tree.insert_alias("/w(/:page)", wiki), None);
+    assert!(tree.find("/w").is_none());
+    assert_eq!(tree.insert_alias("/w", wiki), Some(1));
+    assert_eq!(tree.insert_alias("/w/:page", wiki), Some(2));
+}
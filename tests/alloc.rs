@@ -0,0 +1,90 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+};
+
+use path_tree::{Captures, PathTree};
+
+struct Counting;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|n| n.set(n.get() + 1));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.with(|n| n.set(n.get() + 1));
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+fn allocations<F: FnOnce()>(f: F) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    f();
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+#[test]
+fn find_without_allocations() {
+    let mut tree = PathTree::new();
+    let _ = tree.insert("/", 0);
+    let _ = tree.insert("/users/:id", 1);
+    let _ = tree.insert("/:org/:repo/releases/download/:tag/:filename.:ext", 2);
+    let _ = tree.insert("/:a/:b/:c/:d/:e/:f/:g/:h/:i/:j", 3);
+    let _ = tree.insert("/public/:any*", 4);
+    let frozen = tree.clone().freeze();
+
+    let paths = [
+        "/",
+        "/users/42",
+        "/viz-rs/path-tree/releases/download/v0.8.3/path-tree.tar.gz",
+        "/a/b/c/d/e/f/g/h/i/j",
+        "/public/js/main.js",
+        "/not/found",
+    ];
+
+    // `Path::raws` spills for more than 4 parameters
+    assert!(allocations(|| drop(tree.find("/a/b/c/d/e/f/g/h/i/j"))) > 0);
+
+    let mut captures = Captures::new();
+    // grows the buffer for the route with 10 parameters
+    let _ = tree.find_into("/a/b/c/d/e/f/g/h/i/j", &mut captures);
+
+    let n = allocations(|| {
+        for _ in 0..100 {
+            for path in paths {
+                let mut len = 0;
+                if let Some((_, pieces)) = tree.find_into(path, &mut captures) {
+                    len += captures.params(pieces, path).count();
+                }
+                if let Some((_, pieces)) = frozen.find_into(path, &mut captures) {
+                    len += captures.params(pieces, path).count();
+                }
+                assert_eq!(len, 2 * captures.len());
+            }
+        }
+    });
+    assert_eq!(n, 0);
+
+    let n = allocations(|| {
+        let mut count = 0;
+        for path in &paths[..3] {
+            tree.find_with(path, |_, _| count += 1).unwrap();
+            frozen.find_with(path, |_, _| count += 1).unwrap();
+        }
+        assert_eq!(count, 12);
+    });
+    assert_eq!(n, 0);
+}
@@ -0,0 +1,156 @@
+#![cfg(feature = "cache")]
+#![allow(unused_must_use)]
+
+#[path = "fixtures/github.rs"]
+#[allow(dead_code)]
+mod github;
+use github::*;
+
+use path_tree::{CachedPathTree, PathTree};
+
+#[test]
+fn cached_matches() {
+    let tree = ROUTES_WITH_COLON
+        .iter()
+        .enumerate()
+        .fold(PathTree::new(), |mut tree, (i, r)| {
+            tree.insert(r, i);
+            tree
+        });
+    let cached = CachedPathTree::new(tree.clone(), 16);
+
+    // the second round is served by the cache for the last 16 paths
+    for _ in 0..2 {
+        for u in ROUTES_URLS {
+            assert_eq!(cached.find(u), tree.find(u), "{u}");
+        }
+        assert_eq!(cached.cached(), 16);
+    }
+
+    assert_eq!(cached.find("/404/not/found/at/all"), None);
+    assert_eq!(cached.cached(), 16);
+}
+
+#[test]
+fn cache_eviction() {
+    let mut tree = PathTree::new();
+    tree.insert("/users/:id", 0);
+    let cached = CachedPathTree::new(tree, 2);
+
+    let (_, p) = cached.find("/users/1").unwrap();
+    assert_eq!(p.params(), vec![("id", "1")]);
+    cached.find("/users/2");
+    // `/users/1` becomes the most recent one, `/users/2` is evicted
+    cached.find("/users/1");
+    cached.find("/users/3");
+    assert_eq!(cached.cached(), 2);
+
+    for id in ["1", "2", "3"] {
+        let path = format!("/users/{id}");
+        let (h, p) = cached.find(&path).unwrap();
+        assert_eq!(*h, 0);
+        assert_eq!(p.params(), vec![("id", id)]);
+    }
+
+    let cached = CachedPathTree::new(cached.into_inner(), 0);
+    assert!(cached.find("/users/1").is_some());
+    assert_eq!(cached.cached(), 0);
+}
+
+#[test]
+fn insert_invalidates() {
+    let mut cached = CachedPathTree::new(PathTree::new(), 8);
+    cached.insert("/:name", 0);
+
+    let (h, p) = cached.find("/login").unwrap();
+    assert_eq!(*h, 0);
+    assert_eq!(p.params(), vec![("name", "login")]);
+    assert_eq!(cached.cached(), 1);
+
+    let id = cached.insert("/login", 1);
+    assert_eq!(cached.cached(), 0);
+
+    let (h, p) = cached.find("/login").unwrap();
+    assert_eq!(*h, 1);
+    assert_eq!(*p.id, id);
+    assert!(p.params().is_empty());
+    assert_eq!(cached.tree().get_route(id).unwrap().0, 1);
+}
+
+#[test]
+fn concurrent_readers() {
+    let tree = ROUTES_WITH_COLON
+        .iter()
+        .enumerate()
+        .fold(PathTree::new(), |mut tree, (i, r)| {
+            tree.insert(r, i);
+            tree
+        });
+    let cached = CachedPathTree::new(tree, 64);
+
+    std::thread::scope(|s| {
+        for n in 0..4 {
+            let cached = &cached;
+            s.spawn(move || {
+                for u in ROUTES_URLS.iter().cycle().skip(n * 50).take(1000) {
+                    let (h, p) = cached.find(u).unwrap();
+                    assert_eq!(p.id, h, "{u}");
+                }
+            });
+        }
+    });
+
+    assert_eq!(cached.cached(), 64);
+}
+
+#[test]
+fn cached_aliases() {
+    let mut tree = PathTree::new();
+    let users = tree.insert("/users/:id", 0);
+    tree.insert_alias("/u/:uid", users);
+    let cached = CachedPathTree::new(tree.clone(), 4);
+
+    for _ in 0..2 {
+        for path in ["/u/1", "/users/1"] {
+            assert_eq!(cached.find(path), tree.find(path), "{path}");
+        }
+    }
+    let (_, p) = cached.find("/u/1").unwrap();
+    assert_eq!((*p.id, p.alias), (users, Some(0)));
+    assert_eq!(p.params(), vec![("uid", "1")]);
+}
+
+#[test]
+fn sharded_cache() {
+    let tree = ROUTES_WITH_COLON
+        .iter()
+        .enumerate()
+        .fold(PathTree::new(), |mut tree, (i, r)| {
+            tree.insert(r, i);
+            tree
+        });
+    let cached = CachedPathTree::new(tree.clone(), 1024);
+
+    // the readers miss the same paths at the same time
+    std::thread::scope(|s| {
+        for _ in 0..4 {
+            let (cached, tree) = (&cached, &tree);
+            s.spawn(move || {
+                for _ in 0..2 {
+                    for u in ROUTES_URLS {
+                        assert_eq!(cached.find(u), tree.find(u), "{u}");
+                    }
+                }
+            });
+        }
+    });
+
+    let mut urls = ROUTES_URLS.to_vec();
+    urls.sort_unstable();
+    urls.dedup();
+    assert_eq!(cached.cached(), urls.len());
+
+    let mut cached = cached;
+    cached.insert("/404", usize::MAX);
+    assert_eq!(cached.cached(), 0);
+}
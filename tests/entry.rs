@@ -0,0 +1,94 @@
+#![allow(unused_must_use)]
+
+use path_tree::{Entry, Kind, PathTree, Piece, Position};
+
+#[test]
+fn entries() {
+    let mut tree = PathTree::<Vec<&str>>::new();
+
+    tree.entry("/users/:id").or_default().push("GET");
+    tree.entry("/users/:id").or_default().push("PUT");
+    tree.entry("/users/me").or_insert_with(|| vec!["GET"]);
+    assert_eq!(tree.get_route(0).unwrap().0, vec!["GET", "PUT"]);
+    assert_eq!(tree.get_route(1).unwrap().0, vec!["GET"]);
+
+    // routes of the same shape share the entry, the pieces are kept
+    match tree.entry("/users/:name") {
+        Entry::Occupied(mut entry) => {
+            assert_eq!(entry.id(), 0);
+            assert_eq!(
+                entry.pieces(),
+                [
+                    Piece::String(b"/users/".to_vec()),
+                    Piece::Parameter(Position::Named(b"id".to_vec()), Kind::Normal),
+                ]
+            );
+            assert_eq!(entry.insert(vec!["DELETE"]), vec!["GET", "PUT"]);
+        }
+        Entry::Vacant(_) => unreachable!(),
+    }
+    let (h, p) = tree.find("/users/1").unwrap();
+    assert_eq!(h, &vec!["DELETE"]);
+    assert_eq!(p.params(), vec![("id", "1")]);
+
+    let entry = tree.entry("/users/:id/posts");
+    assert_eq!(entry.id(), None);
+    match entry {
+        Entry::Vacant(entry) => {
+            assert_eq!(entry.pieces().len(), 3);
+            entry.insert(vec!["POST"]);
+        }
+        Entry::Occupied(_) => unreachable!(),
+    }
+    assert_eq!(tree.entry("/users/:id/posts").id(), Some(2));
+
+    tree.entry("/users/me")
+        .and_modify(|methods| methods.push("PATCH"))
+        .or_default();
+    tree.entry("/users")
+        .and_modify(|methods| methods.push("PATCH"))
+        .or_default();
+    assert_eq!(tree.find("/users/me").unwrap().0, &vec!["GET", "PATCH"]);
+    assert_eq!(tree.find("/users").unwrap().0, &Vec::<&str>::new());
+    assert_eq!(tree.iter().count(), 4);
+}
+
+#[test]
+fn no_overwrite() {
+    let mut tree = PathTree::new();
+    tree.insert("/posts/:id", 0);
+    tree.insert("/posts/new", 1);
+
+    // the split node of `/posts/` is not a route
+    assert!(matches!(tree.entry("/posts/"), Entry::Vacant(_)));
+    assert!(matches!(tree.entry("/posts/ne"), Entry::Vacant(_)));
+    assert_eq!(*tree.entry("/posts/new").or_insert(2), 1);
+    assert_eq!(*tree.entry("/posts/:slug").or_insert(2), 0);
+    assert_eq!(tree.find("/posts/new").unwrap().0, &1);
+}
+
+#[test]
+fn expanded_entries() {
+    let mut tree = PathTree::new().with_optional_groups();
+
+    // the groups are expanded like `insert`, the entry is of the first pattern
+    match tree.entry("/v{1,2}/users(/:id)") {
+        Entry::Vacant(entry) => {
+            assert_eq!(entry.pieces().len(), 2);
+            assert_eq!(*entry.insert(0), 0);
+        }
+        Entry::Occupied(_) => unreachable!(),
+    }
+    for path in ["/v1/users/1", "/v1/users", "/v2/users/1", "/v2/users"] {
+        let (h, p) = tree.find(path).unwrap();
+        assert_eq!((*h, p.alias), (0, None), "{path}");
+    }
+    assert_eq!(*tree.entry("/v{1,2}/users(/:id)").or_insert(1), 0);
+    assert_eq!(tree.entry("/v2/users").id(), Some(0));
+    assert_eq!(tree.iter().count(), 1);
+
+    assert_eq!(tree.id_of("/v{1,2}/users(/:id)"), Some(0));
+    assert_eq!(tree.id_of("/v{2,3}/users"), Some(0));
+    assert_eq!(tree.get_by_pattern("/v2/users/:name"), Some((0, &0)));
+    assert!(tree.id_of("/v{3,1}/users").is_none());
+}
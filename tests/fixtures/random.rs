@@ -0,0 +1,55 @@
+use rand::{rngs::StdRng, seq::IndexedRandom, Rng, SeedableRng};
+
+/// The pieces of the random patterns.
+pub const PIECES: [&str; 19] = [
+    "/", "a", "b", "ab", "/a", ":x", ":y?", ":w?-", "*", "+", ":z*", ".", "-", "/:p?", "/*", ":q+",
+    "c/", "\\:", "/:n*/",
+];
+
+/// The pieces of the random alternations, added to [`PIECES`] by the tests covering them.
+pub const ALTERNATIONS: [&str; 2] = [":l(a|ab)", ":m(b|.)"];
+
+/// The pieces of the random paths.
+pub const BYTES: [&str; 8] = ["/", "a", "b", ".", "-", "c", "ab", "/a"];
+
+/// A seeded generator of random patterns and paths.
+///
+/// The seed is taken from the `SEED` variable if set, and printed when the test fails, so the
+/// failure can be replayed with `SEED=<seed> cargo test <test>`.
+pub struct Random {
+    seed: u64,
+    rng: StdRng,
+}
+
+impl Random {
+    pub fn new() -> Self {
+        let seed = std::env::var("SEED")
+            .ok()
+            .and_then(|seed| seed.parse().ok())
+            .unwrap_or_else(rand::random);
+        Self {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// Returns a `/` followed by fewer than `n` of the pieces.
+    pub fn pattern(&mut self, pieces: &[&str], n: usize) -> String {
+        (0..self.rng.random_range(0..n)).fold(String::from("/"), |mut s, _| {
+            s.push_str(pieces.choose(&mut self.rng).unwrap());
+            s
+        })
+    }
+
+    pub fn rng(&mut self) -> &mut StdRng {
+        &mut self.rng
+    }
+}
+
+impl Drop for Random {
+    fn drop(&mut self) {
+        if std::thread::panicking() {
+            eprintln!("random seed: {}", self.seed);
+        }
+    }
+}
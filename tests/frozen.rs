@@ -0,0 +1,179 @@
+#![allow(unused_must_use)]
+
+#[path = "fixtures/github.rs"]
+#[allow(dead_code)]
+mod github;
+use github::*;
+
+#[path = "fixtures/random.rs"]
+#[allow(dead_code)]
+mod random;
+use random::*;
+
+use path_tree::{Captures, PathTree};
+use rand::Rng;
+
+fn assert_same_matches(routes: &[&str], paths: &[&str]) {
+    let mut tree = PathTree::<usize>::new();
+    for (i, r) in routes.iter().enumerate() {
+        tree.insert(r, i);
+    }
+    let frozen = tree.clone().freeze();
+    let binary = frozen.clone().without_jump_tables();
+
+    for path in paths {
+        assert_eq!(tree.find(path), frozen.find(path), "{path}");
+        assert_eq!(tree.find(path), binary.find(path), "{path}");
+    }
+}
+
+#[test]
+fn github_routes() {
+    assert_same_matches(&ROUTES_WITH_COLON, &ROUTES_URLS);
+
+    let tree = ROUTES_WITH_COLON
+        .iter()
+        .enumerate()
+        .fold(PathTree::new(), |mut tree, (i, r)| {
+            tree.insert(r, i);
+            tree
+        })
+        .freeze();
+
+    for (i, u) in ROUTES_URLS.iter().enumerate() {
+        let (h, _) = tree.find(u).unwrap();
+        assert_eq!(h, &i);
+    }
+}
+
+#[test]
+fn mixed_routes() {
+    assert_same_matches(
+        &[
+            "/",
+            "/login",
+            "/signup",
+            "/settings",
+            "/settings/:page",
+            "/:user",
+            "/:user/:repo",
+            "/public/:any*",
+            "/:org/:repo/releases/download/:tag/:filename.:ext",
+            "/:org/:repo/tags/:day-:month-:year",
+            "/:org/:repo/actions/:name\\::verb",
+            "/:org/:repo/:page",
+            "/:org/:repo/*",
+            "/api/+",
+            "/posts/:page?",
+            "/posts/:page?/comments",
+            "/files/*/raw",
+            "/:name+.123",
+            "/:name*.123.456",
+            "/:name.js",
+            "/:name.js.gz",
+        ],
+        &[
+            "",
+            "/",
+            "/login",
+            "/settings",
+            "/settings/admin",
+            "/viz-rs",
+            "/viz-rs/path-tree",
+            "/public/js/main.js",
+            "/public/",
+            "/rust-lang/rust-analyzer/releases/download/2022-09-12/rust-analyzer.gz",
+            "/rust-lang/rust-analyzer/tags/2022-09-12",
+            "/rust-lang/rust-analyzer/actions/ci:bench",
+            "/rust-lang/rust-analyzer/stargazers",
+            "/rust-lang/rust-analyzer/stargazers/404",
+            "/api/",
+            "/api/v1",
+            "/posts",
+            "/posts/",
+            "/posts/2",
+            "/posts/comments",
+            "/posts/2/comments",
+            "/files/raw",
+            "/files/a/b/raw",
+            "/abc.xyz.123",
+            "/abc.xyz.123.456",
+            "/node.js",
+            "/node.js.gz",
+            "/node.js.gz.js.gz",
+            "//",
+            "/a//b",
+        ],
+    );
+}
+
+#[test]
+fn high_fanout() {
+    let routes = ('a'..='z')
+        .map(|c| format!("/{c}{c}"))
+        .chain(["/:name".to_string(), "/z/:name".to_string()])
+        .collect::<Vec<_>>();
+    let routes = routes.iter().map(String::as_str).collect::<Vec<_>>();
+
+    assert_same_matches(
+        &routes,
+        &[
+            "/aa", "/mm", "/zz", "/ab", "/z", "/z/", "/z/a", "/0", "/", "/~",
+        ],
+    );
+}
+
+#[test]
+fn random_routes() {
+    let mut random = Random::new();
+
+    for _ in 0..1000 {
+        let routes = (0..8)
+            .map(|_| random.pattern(&PIECES, 5))
+            .collect::<Vec<_>>();
+        let paths = (0..30)
+            .map(|_| random.pattern(&BYTES, 7))
+            .collect::<Vec<_>>();
+        assert_same_matches(
+            &routes.iter().map(String::as_str).collect::<Vec<_>>(),
+            &paths.iter().map(String::as_str).collect::<Vec<_>>(),
+        );
+    }
+}
+
+#[test]
+fn random_trees() {
+    let pieces = [&PIECES[..], &ALTERNATIONS].concat();
+    let mut random = Random::new();
+    for i in 0..1000 {
+        let mut tree = if i % 2 == 0 {
+            PathTree::new()
+        } else {
+            PathTree::new().with_prefilter()
+        };
+        let routes = (0..8)
+            .map(|_| random.pattern(&pieces, 5))
+            .collect::<Vec<_>>();
+        for (i, r) in routes.iter().enumerate() {
+            tree.insert(r, i);
+        }
+        for _ in 0..3 {
+            let id = random.rng().random_range(0..tree.iter().count());
+            tree.insert_alias(&random.pattern(&pieces, 5), id);
+        }
+        let frozen = tree.clone().freeze();
+
+        let mut captures = Captures::new();
+        let mut frozen_captures = Captures::new();
+        for _ in 0..30 {
+            let path = random.pattern(&BYTES, 7);
+            assert_eq!(frozen.find(&path), tree.find(&path), "{routes:?} {path}");
+            assert_eq!(
+                frozen.find_into(&path, &mut frozen_captures),
+                tree.find_into(&path, &mut captures),
+                "{routes:?} {path}"
+            );
+            assert_eq!(frozen_captures.ranges(), captures.ranges(), "{path}");
+        }
+    }
+}
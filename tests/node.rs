@@ -1,6 +1,6 @@
 #![allow(clippy::too_many_lines)]
 
-use path_tree::{Key, Kind, Node};
+use path_tree::{Key, Kind, Node, PathTree};
 
 #[test]
 fn github_nodes() {
@@ -186,3 +186,35 @@ fn github_nodes() {
 "
     );
 }
+
+#[test]
+fn edited_nodes() {
+    // longer than the routes of `/a`
+    let mut tree = PathTree::new();
+    let _ = tree.insert("/a", 0);
+    tree.node.insert_bytes(b"/abc").value = Some(1);
+    assert_eq!(tree.node.find(b"/abc").map(|(id, _)| *id), Some(1));
+    assert_eq!(tree.node.find(b"/a").map(|(id, _)| *id), Some(0));
+
+    // shorter than the routes of `/a/:id/edit`
+    let mut tree = PathTree::new();
+    let _ = tree.insert("/a/:id/edit", 0);
+    tree.node
+        .insert_bytes(b"/a/")
+        .insert_parameter(Kind::Normal)
+        .value = Some(1);
+    assert_eq!(tree.node.find(b"/a/1").map(|(id, _)| *id), Some(1));
+    assert_eq!(tree.node.find(b"/a/1/edit").map(|(id, _)| *id), Some(0));
+
+    // pushed into the children
+    let mut tree = PathTree::new();
+    let _ = tree.insert("/a", 0);
+    let _ = tree.insert("/b", 1);
+    tree.node
+        .nodes0
+        .as_mut()
+        .unwrap()
+        .push(Node::new(Key::String(b"cc".to_vec()), Some(5)));
+    assert_eq!(tree.node.find(b"/cc").map(|(id, _)| *id), Some(5));
+    assert_eq!(tree.node.find(b"/b").map(|(id, _)| *id), Some(1));
+}
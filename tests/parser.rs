@@ -325,3 +325,117 @@ fn parses() {
         ]
     );
 }
+
+#[test]
+fn expands() {
+    for (pattern, patterns) in [
+        ("/users/:id", vec!["/users/:id"]),
+        ("/v{1,2}/users/:id", vec!["/v1/users/:id", "/v2/users/:id"]),
+        (
+            "/{en,fr,de}/docs/*",
+            vec!["/en/docs/*", "/fr/docs/*", "/de/docs/*"],
+        ),
+        ("/{a,b}/{c,d}", vec!["/a/c", "/a/d", "/b/c", "/b/d"]),
+        ("/{a,b{1,2}}/x", vec!["/a/x", "/b1/x", "/b2/x"]),
+        ("/docs{,/}", vec!["/docs", "/docs/"]),
+        // kept as they are
+        ("/apps/{app_slug}", vec!["/apps/{app_slug}"]),
+        ("/{a,b", vec!["/{a,b"]),
+        ("/a,b}", vec!["/a,b}"]),
+        (r"/\{a,b}", vec![r"/\{a,b}"]),
+        (r"/{a\,b,c}", vec![r"/a\,b", "/c"]),
+        (r"/{a\},b}", vec![r"/a\}", "/b"]),
+        ("/{x{a}y,z}", vec!["/x{a}y", "/z"]),
+        // the parentheses are literal
+        ("/report(.:format)", vec!["/report(.:format)"]),
+        ("/{a,b}(/:id)", vec!["/a(/:id)", "/b(/:id)"]),
+    ] {
+        assert_eq!(Parser::expand(pattern), patterns, "{pattern}");
+    }
+
+    for (pattern, patterns) in [
+        ("/v{1,2}/users/:id", vec!["/v1/users/:id", "/v2/users/:id"]),
+        // optional groups, present then absent
+        ("/report(.:format)", vec!["/report.:format", "/report"]),
+        (
+            "/archive(/:year(/:month))",
+            vec!["/archive/:year/:month", "/archive/:year", "/archive"],
+        ),
+        ("/a(/:x)(/:y)", vec!["/a/:x/:y", "/a/:x", "/a/:y", "/a"]),
+        ("/{a,b}(/:id)", vec!["/a/:id", "/a", "/b/:id", "/b"]),
+        (r"/\(a)", vec![r"/\(a)"]),
+        ("/(a", vec!["/(a"]),
+        ("/:lang(en|fr)", vec!["/:lang(en|fr)"]),
+        (
+            "/:lang(en|fr)(/:page)",
+            vec!["/:lang(en|fr)/:page", "/:lang(en|fr)"],
+        ),
+    ] {
+        assert_eq!(Parser::expand_optional(pattern), patterns, "{pattern}");
+    }
+
+    // up to 256 patterns
+    let braces = "/{a,b}".repeat(8);
+    assert_eq!(Parser::expand(&braces).len(), 256);
+    let groups = "(/:a)".repeat(8);
+    assert_eq!(Parser::expand_optional(&groups).len(), 256);
+
+    assert_eq!(
+        Parser::new(r"/\{a,b\}").collect::<Vec<_>>(),
+        [
+            Piece::String(b"/".to_vec()),
+            Piece::String(b"{".to_vec()),
+            Piece::String(b"a,b".to_vec()),
+            Piece::String(b"}".to_vec()),
+        ]
+    );
+}
+
+#[test]
+fn parses_alternations() {
+    assert_eq!(
+        Parser::new("/:lang(en|fr|de)/docs").collect::<Vec<_>>(),
+        [
+            Piece::String(b"/".to_vec()),
+            Piece::Alternation(
+                Position::Named(b"lang".to_vec()),
+                vec![b"en".to_vec(), b"fr".to_vec(), b"de".to_vec()]
+            ),
+            Piece::String(b"/docs".to_vec()),
+        ]
+    );
+
+    assert_eq!(
+        Parser::new(r"/report.:format(json|c\|sv)").collect::<Vec<_>>(),
+        [
+            Piece::String(b"/report.".to_vec()),
+            Piece::Alternation(
+                Position::Named(b"format".to_vec()),
+                vec![b"json".to_vec(), b"c|sv".to_vec()]
+            ),
+        ]
+    );
+
+    // not alternations
+    assert!(!Parser::new("/:year(/:month)").any(|piece| matches!(piece, Piece::Alternation(..))));
+    assert_eq!(
+        Parser::new("/:a(b").collect::<Vec<_>>(),
+        [
+            Piece::String(b"/".to_vec()),
+            Piece::Parameter(Position::Named(b"a".to_vec()), Kind::Normal),
+            Piece::String(b"(b".to_vec()),
+        ]
+    );
+}
+
+#[test]
+#[should_panic(expected = "expands to more than 256 patterns")]
+fn expands_too_many() {
+    let _ = Parser::expand(&"/{a,b}".repeat(9));
+}
+
+#[test]
+#[should_panic(expected = "expands to more than 256 patterns")]
+fn expands_too_many_optional() {
+    let _ = Parser::expand_optional(&"(/:a)".repeat(64));
+}
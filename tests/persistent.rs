@@ -0,0 +1,254 @@
+#![allow(unused_must_use)]
+
+#[path = "fixtures/github.rs"]
+#[allow(dead_code)]
+mod github;
+use github::*;
+
+#[path = "fixtures/random.rs"]
+#[allow(dead_code)]
+mod random;
+use random::*;
+
+use path_tree::{Key, Parser, PathTree, PersistentPathTree, Piece};
+use rand::seq::{IndexedRandom, SliceRandom};
+
+/// Returns the pieces without the parameter names, routes of the same shape share a node.
+fn shape(route: &str) -> Vec<Result<Vec<u8>, Key>> {
+    Parser::new(route).fold(Vec::new(), |mut shape, piece| {
+        match (piece, shape.last_mut()) {
+            (Piece::String(s), Some(Ok(last))) => last.extend(s),
+            (Piece::String(s), _) => shape.push(Ok(s)),
+            (Piece::Parameter(_, k), _) => shape.push(Err(Key::Parameter(k))),
+            (Piece::Alternation(_, l), _) => shape.push(Err(Key::Alternation(l))),
+        }
+        shape
+    })
+}
+
+#[test]
+fn versions() {
+    let v0 = PersistentPathTree::new();
+    let v1 = v0.insert("/users/:id", 0).insert("/users/me", 1);
+    let v2 = v1.insert("/users/:id", 2).remove("/users/me");
+
+    assert!(v0.is_empty());
+    assert!(v0.find("/users/1").is_none());
+
+    assert_eq!(v1.len(), 2);
+    assert_eq!(v1.find("/users/me").unwrap().0, &1);
+    let (h, p) = v1.find("/users/1").unwrap();
+    assert_eq!((*h, *p.id), (0, 0));
+    assert_eq!(p.params(), vec![("id", "1")]);
+
+    // overwrites keep the id
+    assert_eq!(v2.len(), 1);
+    let (h, p) = v2.find("/users/me").unwrap();
+    assert_eq!((*h, *p.id), (2, 0));
+    assert_eq!(p.params(), vec![("id", "me")]);
+
+    // removing a missing route changes nothing
+    let v3 = v2.remove("/users/:id/posts").remove("/users");
+    assert_eq!(v3.len(), 1);
+    assert_eq!(v3.find("/users/me").unwrap().0, &2);
+
+    // the parameter names are not a part of the route
+    let v4 = v3.remove("/users/:name");
+    assert!(v4.is_empty());
+    assert!(v4.find("/users/me").is_none());
+    assert_eq!(v4.insert("/users/:id", 3).find("/users/me").unwrap().0, &3);
+}
+
+#[test]
+fn github_routes() {
+    let mut tree = PathTree::new();
+    let mut persistent = PersistentPathTree::new();
+    for (i, r) in ROUTES_WITH_COLON.iter().enumerate() {
+        tree.insert(r, i);
+        persistent = persistent.insert(r, i);
+    }
+    assert_eq!(persistent.len(), ROUTES_WITH_COLON.len());
+
+    for (i, u) in ROUTES_URLS.iter().enumerate() {
+        assert_eq!(persistent.find(u), tree.find(u), "{u}");
+        assert_eq!(persistent.find(u).unwrap().0, &i);
+    }
+
+    let mut random = Random::new();
+    let mut removed = (0..ROUTES_WITH_COLON.len()).collect::<Vec<_>>();
+    removed.shuffle(random.rng());
+    removed.truncate(ROUTES_WITH_COLON.len() / 2);
+
+    let next = removed.iter().fold(persistent.clone(), |tree, &i| {
+        tree.remove(ROUTES_WITH_COLON[i])
+    });
+    assert_eq!(next.len(), ROUTES_WITH_COLON.len() - removed.len());
+
+    for (i, u) in ROUTES_URLS.iter().enumerate() {
+        // the previous version is untouched
+        assert_eq!(persistent.find(u).unwrap().0, &i);
+
+        let found = next.find(u).map(|(h, _)| *h);
+        if removed.contains(&i) {
+            assert_ne!(found, Some(i), "{u}");
+        } else {
+            assert_eq!(found, Some(i), "{u}");
+        }
+        if let Some(h) = found {
+            assert!(!removed.contains(&h), "{u}");
+        }
+    }
+}
+
+#[test]
+fn random_routes() {
+    let mut random = Random::new();
+
+    for _ in 0..1000 {
+        let routes = (0..8)
+            .map(|_| random.pattern(&PIECES, 5))
+            .collect::<Vec<_>>();
+        let paths = (0..30)
+            .map(|_| random.pattern(&BYTES, 7))
+            .collect::<Vec<_>>();
+
+        let mut tree = PathTree::new();
+        let mut persistent = PersistentPathTree::new();
+        for (i, r) in routes.iter().enumerate() {
+            tree.insert(r, i);
+            persistent = persistent.insert(r, i);
+        }
+        for path in &paths {
+            assert_eq!(persistent.find(path), tree.find(path), "{routes:?} {path}");
+        }
+
+        // removes the odd routes, the other ones are inserted again in the same order
+        let removed = routes
+            .iter()
+            .skip(1)
+            .step_by(2)
+            .map(|r| shape(r))
+            .collect::<Vec<_>>();
+        let mut tree = PathTree::new();
+        for (i, r) in routes.iter().enumerate() {
+            if i % 2 == 1 {
+                persistent = persistent.remove(r);
+            } else if !removed.contains(&shape(r)) {
+                tree.insert(r, i);
+            }
+        }
+        for path in &paths {
+            assert_eq!(
+                persistent.find(path).map(|(h, p)| (*h, p.raws)),
+                tree.find(path).map(|(h, p)| (*h, p.raws)),
+                "{routes:?} {path}"
+            );
+        }
+    }
+}
+
+#[test]
+fn expanded_routes() {
+    let v1 = PersistentPathTree::new()
+        .insert("/v2/users/:id", 0)
+        .insert("/v{1,2,3}/users/:id", 1);
+
+    // the patterns inserted by other routes are left to them
+    assert_eq!(v1.len(), 3);
+    let (h, p) = v1.find("/v2/users/7").unwrap();
+    assert_eq!((*h, *p.id), (0, 0));
+    for path in ["/v1/users/7", "/v3/users/7"] {
+        let (h, p) = v1.find(path).unwrap();
+        assert_eq!((*h, *p.id), (1, 1), "{path}");
+        assert_eq!(p.params(), vec![("id", "7")]);
+    }
+
+    let v2 = v1.remove("/v{1,3}/users/:id");
+    assert_eq!(v2.len(), 1);
+    assert!(v2.find("/v1/users/7").is_none());
+    assert_eq!(v1.len(), 3);
+
+    // the parentheses are literal unless the optional groups are enabled
+    let v3 = v2.insert("/wiki/Rust_(language)", 2);
+    assert_eq!(v3.find("/wiki/Rust_(language)").unwrap().0, &2);
+    assert!(v3.find("/wiki/Rust_").is_none());
+
+    let v4 = PersistentPathTree::new()
+        .with_optional_groups()
+        .insert("/archive(/:year(/:month))", 3);
+    assert_eq!(v4.len(), 3);
+    let (h, p) = v4.find("/archive/2024").unwrap();
+    assert_eq!((*h, *p.id), (3, 0));
+    assert_eq!(p.params(), vec![("year", "2024")]);
+    assert_eq!(v4.find("/archive").unwrap().1.params(), vec![]);
+    assert!(v4.remove("/archive(/:year(/:month))").is_empty());
+}
+
+#[test]
+fn expanded_routes_overlap() {
+    // only the patterns of the removed route are removed
+    let v1 = PersistentPathTree::new()
+        .insert("/v2/a", "A")
+        .insert("/v{1,2}/a", "B")
+        .remove("/v{1,2}/a");
+    assert_eq!(v1.len(), 1);
+    assert!(v1.find("/v1/a").is_none());
+    assert_eq!(v1.find("/v2/a").unwrap().0, &"A");
+    assert_eq!(v1.remove("/v{1,2}/a").len(), 0);
+
+    // one of the patterns sets the value of the whole route
+    let v2 = PersistentPathTree::new()
+        .insert("/v{1,2}/a", "A")
+        .insert("/v1/a", "B");
+    assert_eq!(v2.len(), 2);
+    for path in ["/v1/a", "/v2/a"] {
+        let (h, p) = v2.find(path).unwrap();
+        assert_eq!((*h, *p.id), ("B", 0), "{path}");
+    }
+    let v3 = v2.insert("/v2/a", "C");
+    assert_eq!(v3.find("/v1/a").unwrap().0, &"C");
+    assert_eq!(v2.find("/v1/a").unwrap().0, &"B");
+
+    // a removed pattern is not inserted back by the others
+    let v4 = v3.remove("/v2/a").insert("/v1/a", "D");
+    assert_eq!(v4.len(), 1);
+    assert!(v4.find("/v2/a").is_none());
+    assert_eq!(v4.find("/v1/a").unwrap().0, &"D");
+}
+
+#[test]
+fn expanded_random_routes() {
+    let pieces = [&PIECES[..], &["{a,b}", "{/:x,/c}", "/{a,:y}"]].concat();
+    let mut random = Random::new();
+
+    for _ in 0..1000 {
+        let routes = (0..8)
+            .map(|_| random.pattern(&pieces, 5))
+            .collect::<Vec<_>>();
+        let paths = (0..30)
+            .map(|_| random.pattern(&BYTES, 7))
+            .collect::<Vec<_>>();
+
+        let mut tree = PathTree::new();
+        let mut persistent = PersistentPathTree::new();
+        for (i, r) in routes.iter().enumerate() {
+            tree.insert(r, i);
+            persistent = persistent.insert(r, i);
+        }
+        // one of the patterns of some routes is inserted again with a new value
+        for (i, r) in routes.iter().enumerate().step_by(3) {
+            let pattern = Parser::expand(r).choose(random.rng()).unwrap().clone();
+            tree.insert(&pattern, i + 8);
+            persistent = persistent.insert(&pattern, i + 8);
+        }
+        for path in &paths {
+            assert_eq!(persistent.find(path), tree.find(path), "{routes:?} {path}");
+        }
+    }
+}
+
+#[test]
+#[should_panic(expected = "expands to more than 256 patterns")]
+fn expanded_routes_too_many() {
+    let _ = PersistentPathTree::new().insert(&"/{a,b}".repeat(9), 0);
+}
@@ -0,0 +1,127 @@
+#![allow(unused_must_use)]
+
+#[path = "fixtures/github.rs"]
+#[allow(dead_code)]
+mod github;
+use github::*;
+
+#[path = "fixtures/random.rs"]
+#[allow(dead_code)]
+mod random;
+use random::*;
+
+use path_tree::PathTree;
+
+#[test]
+fn rejects_scanners() {
+    let mut tree = PathTree::new().with_prefilter();
+    tree.insert("/", 0);
+    tree.insert("/login", 1);
+    tree.insert("/api/v1/users/:id", 2);
+    tree.insert("/api/v1/users/:id/posts/:page?", 3);
+    tree.insert("/public/:any*", 4);
+    tree.insert("/posts/:page?", 5);
+
+    let prefilter = tree.prefilter().unwrap();
+    // `/` covers all
+    assert_eq!(prefilter.len(), 1);
+    assert!(prefilter.accepts("/wp-admin/install.php"));
+
+    let mut tree = PathTree::new();
+    tree.insert("/login", 1);
+    tree.insert("/api/v1/users/:id", 2);
+    tree.insert("/api/v1/users/:id/posts/:page?", 3);
+    tree.insert("/public/:any*", 4);
+    tree.insert("/posts/:page?", 5);
+    let mut tree = tree.with_prefilter();
+    tree.insert("/api/v1/:resource", 6);
+
+    let prefilter = tree.prefilter().unwrap();
+    assert_eq!(prefilter.len(), 4);
+
+    for path in [
+        "/login",
+        "/api/v1/users/1",
+        "/api/v1/users/1/posts/2",
+        "/api/v1/groups",
+        "/public/",
+        "/posts/1",
+    ] {
+        assert!(prefilter.accepts(path), "{path}");
+        assert!(tree.find(path).is_some(), "{path}");
+    }
+
+    for path in [
+        "/.env",
+        "/wp-admin/install.php",
+        "/api/v2/users/1",
+        "/pos",
+        "/",
+        "wp-login.php",
+    ] {
+        assert!(!prefilter.accepts(path), "{path}");
+        assert!(tree.find(path).is_none(), "{path}");
+    }
+
+    let frozen = tree.freeze();
+    assert!(frozen.prefilter().is_some());
+    assert!(frozen.find("/.env").is_none());
+    assert_eq!(frozen.find("/posts/2").unwrap().0, &5);
+}
+
+#[test]
+fn github_routes() {
+    let tree = ROUTES_WITH_COLON.iter().enumerate().fold(
+        PathTree::new().with_prefilter(),
+        |mut tree, (i, r)| {
+            tree.insert(r, i);
+            tree
+        },
+    );
+
+    for (i, u) in ROUTES_URLS.iter().enumerate() {
+        let (h, _) = tree.find(u).unwrap();
+        assert_eq!(h, &i);
+    }
+}
+
+#[test]
+fn no_false_negatives() {
+    let pieces = [&PIECES[..], &["/b/"]].concat();
+    let mut random = Random::new();
+
+    for n in 0..1000 {
+        let routes = (0..8)
+            .map(|_| random.pattern(&pieces, 6))
+            .collect::<Vec<_>>();
+        let paths = (0..30)
+            .map(|_| random.pattern(&BYTES, 7))
+            .collect::<Vec<_>>();
+
+        let mut tree = PathTree::<usize>::new();
+        for (i, r) in routes.iter().enumerate() {
+            tree.insert(r, i);
+        }
+        // half of the trees keep the prefilter up to date on insert
+        let filtered = if n % 2 == 0 {
+            tree.clone().with_prefilter()
+        } else {
+            routes
+                .iter()
+                .enumerate()
+                .fold(PathTree::new().with_prefilter(), |mut tree, (i, r)| {
+                    tree.insert(r, i);
+                    tree
+                })
+        };
+        let prefilter = filtered.prefilter().unwrap();
+
+        for path in &paths {
+            let found = tree.find(path);
+            if found.is_some() {
+                assert!(prefilter.accepts(path), "{routes:?} {path}");
+            }
+            assert_eq!(found, filtered.find(path), "{routes:?} {path}");
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use path_tree::{Rewriter, UrlError};
+
+#[test]
+fn rewrites() {
+    let mut rewriter = Rewriter::new();
+    rewriter
+        .insert("/old/:org/:repo/:rest*", "/:org/:repo/tree/:rest*")
+        .unwrap();
+    rewriter.insert("/v1/*", "/v2/*").unwrap();
+    rewriter.insert("/api/+/raw/*", "/raw/+/*").unwrap();
+    rewriter.insert("/u/:id", "/users/:id").unwrap();
+    rewriter.insert("/docs/:page?", "/guide/:page?").unwrap();
+    rewriter.insert("/home", "/").unwrap();
+
+    for (target, rewritten) in [
+        (
+            "/old/viz-rs/path-tree/src/lib.rs",
+            "/viz-rs/path-tree/tree/src/lib.rs",
+        ),
+        ("/old/viz-rs/path-tree", "/viz-rs/path-tree/tree"),
+        ("/v1/users/1", "/v2/users/1"),
+        ("/api/a/b/raw/c", "/raw/a/b/c"),
+        ("/u/1?tab=repos#top", "/users/1?tab=repos"),
+        ("/u/a%20b", "/users/a%20b"),
+        ("/docs", "/guide"),
+        ("/docs/intro", "/guide/intro"),
+        ("/home", "/"),
+    ] {
+        assert_eq!(
+            rewriter.rewrite(target).as_deref(),
+            Some(rewritten),
+            "{target}"
+        );
+    }
+    assert_eq!(rewriter.rewrite("/users/1"), None);
+    assert_eq!(rewriter.tree().iter().count(), 6);
+}
+
+#[test]
+fn unknown_params() {
+    let mut rewriter = Rewriter::new();
+    assert_eq!(
+        rewriter.insert("/u/:id", "/users/:name"),
+        Err(UrlError::Unknown("name".to_string()))
+    );
+    assert_eq!(
+        rewriter.insert("/u/:id", "/users/:id/*"),
+        Err(UrlError::Unknown("*1".to_string()))
+    );
+    assert_eq!(
+        UrlError::Unknown("name".to_string()).to_string(),
+        "unknown parameter `name`"
+    );
+    assert_eq!(rewriter.rewrite("/u/1"), None);
+}
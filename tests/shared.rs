@@ -0,0 +1,66 @@
+#![cfg(feature = "std")]
+#![allow(unused_must_use)]
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use path_tree::{PathTree, SharedPathTree};
+
+#[test]
+fn snapshots() {
+    let mut tree = PathTree::new();
+    tree.insert("/users/:id", 0);
+    let shared = SharedPathTree::new(tree);
+
+    let snapshot = shared.snapshot();
+    let (h, p) = snapshot.find("/users/1").unwrap();
+
+    let id = shared.update(|tree| tree.insert("/users/me", 1));
+    assert_eq!(id, 1);
+
+    // the old version stays alive with its matches
+    assert_eq!(*h, 0);
+    assert_eq!(p.params(), vec![("id", "1")]);
+    assert_eq!(snapshot.find("/users/me").unwrap().0, &0);
+
+    let snapshot = shared.snapshot();
+    assert_eq!(snapshot.find("/users/me").unwrap().0, &1);
+    let kept = snapshot.into_arc();
+
+    // removes by publishing a rebuilt tree
+    let mut tree = PathTree::new();
+    tree.insert("/users/me", 1);
+    shared.store(tree);
+
+    assert!(shared.snapshot().find("/users/1").is_none());
+    assert_eq!(kept.find("/users/1").unwrap().0, &0);
+}
+
+#[test]
+fn concurrent_readers() {
+    let shared = SharedPathTree::<usize>::default();
+    let done = AtomicBool::new(false);
+
+    std::thread::scope(|s| {
+        for _ in 0..4 {
+            s.spawn(|| {
+                while !done.load(Ordering::Acquire) {
+                    let snapshot = shared.snapshot();
+                    // each version has the routes `/0` to `/n` as a whole
+                    let n = snapshot.iter().count();
+                    for i in 0..n {
+                        let (h, _) = snapshot.find(&format!("/{i}")).unwrap();
+                        assert_eq!(*h, i);
+                    }
+                    assert!(snapshot.find(&format!("/{n}")).is_none());
+                }
+            });
+        }
+
+        for i in 0..200 {
+            shared.update(|tree| tree.insert(&format!("/{i}"), i));
+        }
+        done.store(true, Ordering::Release);
+    });
+
+    assert_eq!(shared.snapshot().iter().count(), 200);
+}
@@ -1,7 +1,12 @@
 #![allow(unused_must_use)]
 #![allow(clippy::too_many_lines)]
 
-use path_tree::{Kind, PathTree, Piece, Position};
+#[path = "fixtures/random.rs"]
+#[allow(dead_code)]
+mod random;
+use random::*;
+
+use path_tree::{Kind, PathTree, Piece, Position, UrlError};
 use rand::seq::SliceRandom;
 
 #[test]
@@ -224,7 +229,7 @@ fn multi_named_parameters() {
 fn catch_all_parameter() {
     //  Pattern: /src/*filepath
     //
-    //      /src                      no match
+    //      /src                      match
     //      /src/                     match
     //      /src/somefile.go          match
     //      /src/subdir/somefile.go   match
@@ -233,7 +238,7 @@ fn catch_all_parameter() {
     tree.insert("/src/:filepath*", "* files");
 
     let res = vec![
-        ("/src", false, vec![]),
+        ("/src", true, vec![("filepath", "")]),
         ("/src/", true, vec![("filepath", "")]),
         ("/src/somefile.rs", true, vec![("filepath", "somefile.rs")]),
         (
@@ -492,7 +497,9 @@ fn match_params() {
 
     tree.insert("/api/v1/:param/*", 1);
 
-    assert_eq!(tree.find("/api/v1/entity"), None);
+    let (h, p) = tree.find("/api/v1/entity").unwrap();
+    assert_eq!(*h, 1);
+    assert_eq!(p.params(), vec![("param", "entity"), ("*1", "")]);
     let (h, p) = tree.find("/api/v1/entity/").unwrap();
     assert_eq!(*p.id, 0);
     assert_eq!(*h, 1);
@@ -644,7 +651,10 @@ fn match_params() {
     assert_eq!(*h, 1);
     assert_eq!(p.params(), vec![("param", "test"), ("*1", "")]);
 
-    assert_eq!(tree.find("/v1/some/resource/name:customVerb??/test"), None);
+    let (_, p) = tree
+        .find("/v1/some/resource/name:customVerb??/test")
+        .unwrap();
+    assert_eq!(p.params(), vec![("param", "test"), ("*1", "")]);
 
     // /
     // └── api/v1/
@@ -653,7 +663,10 @@ fn match_params() {
 
     tree.insert("/api/v1/*", 1);
 
-    assert_eq!(tree.find("/api/v1"), None);
+    // the `/` is skipped when the segment is left out
+    let (h, p) = tree.find("/api/v1").unwrap();
+    assert_eq!(*h, 1);
+    assert_eq!(p.params(), vec![("*1", "")]);
 
     let (h, p) = tree.find("/api/v1/").unwrap();
     assert_eq!(*h, 1);
@@ -1291,7 +1304,10 @@ fn match_params() {
     let (_, p) = tree.find("/api/v1/well/abc/").unwrap();
     assert_eq!(p.params(), vec![("param", "well"), ("*1", "")]);
 
-    assert_eq!(tree.find("/api/v1/well/abc"), None);
+    let (_, p) = tree.find("/api/v1/well/abc").unwrap();
+    assert_eq!(p.params(), vec![("param", "well"), ("*1", "")]);
+
+    assert_eq!(tree.find("/api/v1/well/ab"), None);
     assert_eq!(tree.find("/api/v1/well/ttt"), None);
 
     // /
@@ -1305,7 +1321,8 @@ fn match_params() {
 
     tree.insert("/api/:day/:month?/:year?", 1);
 
-    assert_eq!(tree.find("/api/1"), None);
+    let (_, p) = tree.find("/api/1").unwrap();
+    assert_eq!(p.params(), vec![("day", "1"), ("month", ""), ("year", "")]);
 
     let (h, p) = tree.find("/api/1/").unwrap();
     assert_eq!(h, &1);
@@ -1329,7 +1346,8 @@ fn match_params() {
     let (_, p) = tree.find("/api/1/-/").unwrap();
     assert_eq!(p.params(), vec![("day", "1"), ("month", "-"), ("year", "")]);
 
-    assert_eq!(tree.find("/api/1-"), None);
+    let (_, p) = tree.find("/api/1-").unwrap();
+    assert_eq!(p.params(), vec![("day", "1-"), ("month", ""), ("year", "")]);
 
     let (_, p) = tree.find("/api/1-/").unwrap();
     assert_eq!(p.params(), vec![("day", "1-"), ("month", ""), ("year", "")]);
@@ -2314,3 +2332,829 @@ fn test_dots_ext_no_qualifier() {
 
     assert_eq!(params.params(), &[("name", "node.js.gz")]);
 }
+
+#[test]
+fn static_routes_keep_precedence() {
+    let mut tree = PathTree::new();
+    let _ = tree.insert("/users/:id", 0);
+    let _ = tree.insert("/users/new", 1);
+    let _ = tree.insert("/users/new/:tab?", 2);
+    let _ = tree.insert("/a", 3);
+    let _ = tree.insert("b", 4);
+    let _ = tree.insert("/users/new", 5);
+
+    let (value, path) = tree.find("/users/new").unwrap();
+    assert_eq!(value, &5);
+    assert_eq!(path.id, &1);
+    assert!(path.raws.is_empty());
+
+    let (value, path) = tree.find("/users/newer").unwrap();
+    assert_eq!(value, &0);
+    assert_eq!(path.params(), vec![("id", "newer")]);
+
+    // unreachable by the walk, so must not be reachable by the index either
+    assert!(tree.find("b").is_none());
+    assert!(tree.clone().freeze().find("b").is_none());
+    assert_eq!(tree.clone().freeze().find("/users/new").unwrap().0, &5);
+}
+
+#[test]
+fn length_bounds() {
+    let mut tree = PathTree::new();
+    let _ = tree.insert("/files/:path*/raw.tar.gz", 0);
+    let _ = tree.insert("/files/:name.zip", 1);
+    let _ = tree.insert("/:x/a", 2);
+    let _ = tree.insert("/short", 3);
+
+    assert!(tree.find("/files/raw.tar.g").is_none());
+    assert_eq!(
+        tree.find("/files/a/b/raw.tar.gz").unwrap().1.params(),
+        vec![("path", "a/b")]
+    );
+    assert_eq!(
+        tree.find("/files/.zip").unwrap().1.params(),
+        vec![("name", "")]
+    );
+    assert_eq!(tree.find("//a").unwrap().1.params(), vec![("x", "")]);
+    assert!(tree.find("/shor").is_none());
+    assert!(tree.find("/shorter").is_none());
+    assert_eq!(tree.find("/short").unwrap().0, &3);
+}
+
+#[test]
+fn find_into_captures() {
+    let mut tree = PathTree::new();
+    let _ = tree.insert("/users/:id", 0);
+    let _ = tree.insert("/:org/:repo/tags/:day-:month-:year", 1);
+    let _ = tree.insert("/about", 2);
+
+    let mut captures = path_tree::Captures::new();
+
+    let path = "/rust-lang/rust-analyzer/tags/2022-09-12";
+    let (value, pieces) = tree.find_into(path, &mut captures).unwrap();
+    assert_eq!(value, &1);
+    assert_eq!(captures.id(), 1);
+    assert_eq!(captures.len(), 5);
+    assert_eq!(captures.get(0, path), Some("rust-lang"));
+    assert_eq!(captures.ranges()[4], 38..40);
+    assert_eq!(
+        captures.params(pieces, path).collect::<Vec<_>>(),
+        tree.find(path).unwrap().1.params()
+    );
+
+    let (value, pieces) = tree.find_into("/about", &mut captures).unwrap();
+    assert_eq!(value, &2);
+    assert!(pieces.len() == 1 && captures.is_empty());
+
+    assert!(tree.find_into("/users", &mut captures).is_none());
+    assert!(captures.is_empty());
+
+    let mut params = Vec::new();
+    let value = tree.find_with("/users/42", |k, v| {
+        params.push((k.to_string(), v.to_string()));
+    });
+    assert_eq!(value, Some(&0));
+    assert_eq!(params, vec![("id".to_string(), "42".to_string())]);
+}
+
+#[test]
+fn find_many_batch() {
+    let mut tree = PathTree::new();
+    let _ = tree.insert("/", 0);
+    let _ = tree.insert("/users/:id", 1);
+    let _ = tree.insert("/users/:id/repos/:repo", 2);
+
+    let paths = [
+        "/users/1",
+        "/users/1",
+        "/",
+        "/nope",
+        "/users/2/repos/path-tree",
+        "/users/2/repos/path-tree",
+        "/users/3",
+    ];
+
+    let matches = tree.find_many(paths);
+    assert_eq!(matches.len(), 7);
+    for (i, path) in paths.iter().enumerate() {
+        let found = tree.find(path);
+        assert_eq!(matches.id(i), found.as_ref().map(|(v, _)| **v));
+        assert_eq!(
+            matches
+                .captures(i)
+                .iter()
+                .map(|r| &path[r.clone()])
+                .collect::<Vec<_>>(),
+            found.map(|(_, p)| p.raws.to_vec()).unwrap_or_default()
+        );
+    }
+    assert_eq!(matches.iter().flatten().count(), 6);
+
+    assert_eq!(
+        tree.classify(paths),
+        vec![Some(1), Some(1), Some(0), None, Some(2), Some(2), Some(1)]
+    );
+    assert_eq!(tree.clone().freeze().find_many(paths), matches);
+}
+
+#[test]
+fn find_many_shared_prefixes() {
+    let pieces = [&PIECES[..], &ALTERNATIONS[..1]].concat();
+    let mut random = Random::new();
+    for i in 0..500 {
+        let mut tree = if i % 2 == 0 {
+            PathTree::new()
+        } else {
+            PathTree::new().with_prefilter()
+        };
+        for i in 0..8 {
+            tree.insert(&random.pattern(&pieces, 5), i);
+        }
+        let mut paths = (0..40)
+            .map(|_| random.pattern(&BYTES, 7))
+            .collect::<Vec<_>>();
+        paths.sort_unstable();
+        let paths = paths.iter().map(String::as_str).collect::<Vec<_>>();
+
+        let matches = tree.find_many(paths.iter().copied());
+        for (i, path) in paths.iter().enumerate() {
+            let found = tree.find(path);
+            assert_eq!(matches.id(i), found.as_ref().map(|(_, p)| *p.id), "{path}");
+            assert_eq!(
+                matches
+                    .captures(i)
+                    .iter()
+                    .map(|r| &path[r.clone()])
+                    .collect::<Vec<_>>(),
+                found.map(|(_, p)| p.raws.to_vec()).unwrap_or_default(),
+                "{path}"
+            );
+        }
+        assert_eq!(tree.clone().freeze().find_many(paths), matches);
+    }
+}
+
+#[test]
+fn one_or_more_not_empty() {
+    let mut tree = PathTree::new();
+    let _ = tree.insert("/+aa", 0);
+    let _ = tree.insert("/+a.", 1);
+
+    assert!(tree.find("/a.").is_none());
+    assert!(tree.find("/aa").is_none());
+
+    let (h, p) = tree.find("/xa.").unwrap();
+    assert_eq!(*h, 1);
+    assert_eq!(p.params(), vec![("+1", "x")]);
+    assert_eq!(tree.clone().freeze().find("/a."), None);
+}
+
+#[test]
+fn consecutive_escapes() {
+    let mut tree = PathTree::new();
+    let a = tree.insert("/+\\:", 0);
+    let b = tree.insert("/+\\:\\:", 1);
+    assert_ne!(a, b);
+
+    assert_eq!(tree.find("/x:").unwrap().0, &0);
+    assert_eq!(tree.find("/x::").unwrap().0, &1);
+}
+
+fn assert_same_tree(routes: &[&str], paths: &[&str]) {
+    let mut tree = PathTree::new();
+    for (i, r) in routes.iter().enumerate() {
+        tree.insert(r, i);
+    }
+    let built = PathTree::from_sorted(routes.iter().copied().zip(0..));
+
+    assert_eq!(
+        format!("{:?}", built.node),
+        format!("{:?}", tree.node),
+        "{routes:?}"
+    );
+    assert_eq!(
+        built.iter().collect::<Vec<_>>(),
+        tree.iter().collect::<Vec<_>>(),
+        "{routes:?}"
+    );
+    for path in paths {
+        assert_eq!(built.find(path), tree.find(path), "{routes:?} {path}");
+    }
+}
+
+#[test]
+fn from_sorted() {
+    let mut routes = [
+        "/",
+        "/login",
+        "/signup",
+        "/settings",
+        "/settings/:page",
+        "/:user",
+        "/:user/:repo",
+        "/public/:any*",
+        "/:org/:repo/releases/download/:tag/:filename.:ext",
+        "/:org/:repo/tags/:day-:month-:year",
+        "/:org/:repo/actions/:name\\::verb",
+        "/:org/:repo/:page",
+        "/:org/:repo/*",
+        "/api/+",
+        "/api/\\:\\:",
+        "/:username",
+    ];
+    let paths = [
+        "/",
+        "/login",
+        "/settings/admin",
+        "/viz-rs/path-tree",
+        "/public/js/main.js",
+        "/rust-lang/rust-analyzer/actions/ci:bench",
+        "/api/::",
+        "/api/v1",
+    ];
+    assert_same_tree(&routes, &paths);
+
+    routes.sort_unstable();
+    assert_same_tree(&routes, &paths);
+
+    // the routes not starting with the same byte are inserted one by one
+    assert_same_tree(&["/a", "b", "/c"], &["/a", "b", "/b", "/c"]);
+    assert_same_tree(&[":a", "/b"], &["/b", "a", "/a"]);
+    assert_same_tree(&["", "/"], &["", "/"]);
+    assert_same_tree(&[], &["", "/"]);
+
+    let tree = routes.iter().map(|r| (*r, *r)).collect::<PathTree<_>>();
+    assert_eq!(tree.find("/api/v1").unwrap().0, &"/api/+");
+
+    let mut tree = PathTree::new();
+    tree.extend([("/", 0), ("/:user", 1)]);
+    tree.extend([("/login", 2)]);
+    assert_eq!(tree.find("/login").unwrap().0, &2);
+    assert_eq!(tree.find("/viz-rs").unwrap().0, &1);
+}
+
+#[test]
+fn from_sorted_random() {
+    let mut random = Random::new();
+
+    for n in 0..1000 {
+        let mut routes = (0..12)
+            .map(|_| random.pattern(&PIECES, 5))
+            .collect::<Vec<_>>();
+        if n % 2 == 0 {
+            routes.sort_unstable();
+        }
+        let paths = (0..30)
+            .map(|_| random.pattern(&BYTES, 7))
+            .collect::<Vec<_>>();
+        assert_same_tree(
+            &routes.iter().map(String::as_str).collect::<Vec<_>>(),
+            &paths.iter().map(String::as_str).collect::<Vec<_>>(),
+        );
+    }
+}
+
+#[test]
+fn mutable_lookups() {
+    let mut tree = PathTree::new();
+    tree.insert("/users/:id", 0);
+    tree.insert("/users/me", 0);
+    tree.insert("/posts/*", 0);
+
+    let (h, p) = tree.find_mut("/users/1").unwrap();
+    *h += 1;
+    assert_eq!(*p.id, 0);
+    assert_eq!(p.params(), vec![("id", "1")]);
+    *tree.find_mut("/users/me").unwrap().0 += 2;
+    assert!(tree.find_mut("/").is_none());
+
+    let (h, pieces) = tree.get_route_mut(2).unwrap();
+    *h = 3;
+    assert_eq!(pieces.len(), 2);
+    assert!(tree.get_route_mut(3).is_none());
+
+    for (h, _) in &mut tree {
+        *h *= 10;
+    }
+    assert_eq!(
+        tree.iter_mut().rev().map(|(h, _)| *h).collect::<Vec<_>>(),
+        [30, 20, 10]
+    );
+}
+
+#[test]
+fn retain() {
+    let mut tree = PathTree::new().with_prefilter();
+    tree.insert("/users/:id", "user");
+    tree.insert("/users/me", "me");
+    tree.insert("/users/:id/posts", "posts");
+    tree.insert("/posts/*", "post");
+
+    tree.retain(|id, h, _| {
+        *h = if id == 0 { "users" } else { h };
+        id % 2 == 0
+    });
+    assert_eq!(tree.iter().count(), 2);
+
+    // the static route is gone from the index and the nodes
+    let (h, p) = tree.find("/users/me").unwrap();
+    assert_eq!((*h, *p.id), ("users", 0));
+    assert_eq!(p.params(), vec![("id", "me")]);
+    assert!(tree.find("/posts/1").is_none());
+    assert!(tree.prefilter().unwrap().accepts("/users/2"));
+    assert!(!tree.prefilter().unwrap().accepts("/posts/1"));
+
+    let (h, p) = tree.find("/users/1/posts").unwrap();
+    assert_eq!((*h, *p.id), ("posts", 1));
+
+    // the ids keep counting from the retained routes
+    assert_eq!(tree.insert("/posts", "posts"), 2);
+
+    tree.retain(|_, _, _| false);
+    assert_eq!(tree.iter().count(), 0);
+    assert!(tree.find("/users/1/posts").is_none());
+    assert_eq!(tree.insert("/users/:id", "user"), 0);
+    assert_eq!(tree.find("/users/1").unwrap().0, &"user");
+}
+
+#[test]
+fn retain_random() {
+    let mut random = Random::new();
+
+    for n in 0..1000 {
+        let routes = (0..12)
+            .map(|_| random.pattern(&PIECES, 5))
+            .collect::<Vec<_>>();
+        let paths = (0..30)
+            .map(|_| random.pattern(&BYTES, 7))
+            .collect::<Vec<_>>();
+
+        let mut tree = PathTree::new();
+        if n % 2 == 0 {
+            tree = tree.with_prefilter();
+        }
+        for (i, r) in routes.iter().enumerate() {
+            tree.insert(r, i);
+        }
+        tree.retain(|id, _, _| id % 3 != 1);
+
+        // the same as inserting the retained routes
+        let mut fresh = PathTree::new();
+        for (h, pieces) in &tree {
+            fresh.insert(&routes[*h], *h);
+            assert_eq!(&fresh.iter().last().unwrap().1, pieces);
+        }
+        for path in &paths {
+            assert_eq!(tree.find(path), fresh.find(path), "{routes:?} {path}");
+        }
+    }
+}
+
+#[test]
+fn lookup_by_pattern() {
+    const ROUTES: [&str; 9] = [
+        "/",
+        "/users",
+        "/users/:name",
+        "/users/:name/repos",
+        "/users/:name/repos/:page?",
+        "/users/octocat",
+        "/public/*",
+        "/api/+",
+        "/\\:\\:",
+    ];
+
+    let mut tree = PathTree::new();
+    for (i, r) in ROUTES.iter().enumerate() {
+        tree.insert(r, i);
+    }
+    for (i, r) in ROUTES.iter().enumerate() {
+        assert_eq!(tree.id_of(r), Some(i), "{r}");
+        assert_eq!(tree.get_by_pattern(r), Some((i, &i)), "{r}");
+    }
+
+    // the pattern is walked by its pieces, not matched as a path
+    assert_eq!(tree.id_of("/users/:user"), Some(2));
+    assert_eq!(tree.id_of("/users/:user/repos/:n?"), Some(4));
+    assert_eq!(tree.id_of("/public/:file*"), Some(6));
+    assert!(tree.id_of("/users/1").is_none());
+    assert!(tree.id_of("/user").is_none());
+    assert!(tree.id_of("/users/:name/repos/:page").is_none());
+    assert!(tree.get_by_pattern("/public/+").is_none());
+    assert!(tree.get_by_pattern("/::").is_none());
+}
+
+#[test]
+fn named_routes() {
+    let mut tree = PathTree::new();
+    tree.insert_named("repo", "/:org/:repo", 0);
+    tree.insert_named(
+        "repo.release",
+        "/:org/:repo/releases/download/:tag/:filename.:ext",
+        1,
+    );
+    tree.insert_named("public", "/public/*", 2);
+    tree.insert_named("api", "/api/+", 3);
+
+    assert_eq!(
+        tree.url_for_name(
+            "repo.release",
+            &[
+                ("tag", "v1"),
+                ("org", "viz-rs"),
+                ("ext", "gz"),
+                ("repo", "path-tree"),
+                ("filename", "path-tree")
+            ]
+        ),
+        Some("/viz-rs/path-tree/releases/download/v1/path-tree.gz".to_string())
+    );
+    assert_eq!(
+        tree.url_for_name("public", &[("*1", "js/main.js")]),
+        Some("/public/js/main.js".to_string())
+    );
+    assert_eq!(
+        tree.url_for_name("api", &[("+1", "v1")]),
+        Some("/api/v1".to_string())
+    );
+    // the missing params are skipped
+    assert_eq!(
+        tree.url_for_name("repo", &[("org", "viz-rs")]),
+        Some("/viz-rs/".to_string())
+    );
+    assert_eq!(tree.url_for_name("repos", &[]), None);
+
+    // the names follow the retained routes
+    tree.retain(|id, _, _| id != 0);
+    assert_eq!(tree.url_for_name("repo", &[]), None);
+    assert_eq!(
+        tree.url_for_name("public", &[("*1", "a")]),
+        Some("/public/a".to_string())
+    );
+    assert_eq!(tree.insert_named("repo", "/:org/:repo", 4), 3);
+    assert_eq!(
+        tree.url_for_name("repo", &[("repo", "path-tree"), ("org", "viz-rs")]),
+        Some("/viz-rs/path-tree".to_string())
+    );
+}
+
+#[test]
+fn try_url_for() {
+    use path_tree::UrlError;
+
+    let mut tree = PathTree::new();
+    let user = tree.insert("/users/:id", 0);
+    let file = tree.insert("/:org/:repo/releases/download/:tag/:filename.:ext", 1);
+    let public = tree.insert("/public/*", 2);
+    let api = tree.insert("/api/+", 3);
+    let page = tree.insert("/posts/:page?-:size?", 4);
+
+    assert_eq!(tree.try_url_for(user, &["1"]), Ok("/users/1".to_string()));
+    assert_eq!(
+        tree.try_url_for(user, &["a b?#%"]),
+        Ok("/users/a%20b%3F%23%25".to_string())
+    );
+    assert_eq!(
+        tree.try_url_for(user, &["ö@:"]),
+        Ok("/users/%C3%B6@:".to_string())
+    );
+    assert_eq!(
+        tree.try_url_for(file, &["viz-rs", "path-tree", "v1", "path-tree", "tar.gz"]),
+        Ok("/viz-rs/path-tree/releases/download/v1/path-tree.tar.gz".to_string())
+    );
+    assert_eq!(
+        tree.try_url_for(public, &["js/main app.js"]),
+        Ok("/public/js/main%20app.js".to_string())
+    );
+    assert_eq!(tree.try_url_for(public, &[]), Ok("/public".to_string()));
+    assert_eq!(
+        tree.try_url_for(api, &["v1/users"]),
+        Ok("/api/v1/users".to_string())
+    );
+    assert_eq!(
+        tree.try_url_for(page, &["1", "20"]),
+        Ok("/posts/1-20".to_string())
+    );
+    assert_eq!(tree.try_url_for(page, &["1"]), Ok("/posts/1-".to_string()));
+
+    assert_eq!(tree.try_url_for(9, &[]), Err(UrlError::NotFound));
+    assert_eq!(
+        tree.try_url_for(user, &[]),
+        Err(UrlError::Missing("id".to_string()))
+    );
+    assert_eq!(
+        tree.try_url_for(file, &["viz-rs", "path-tree", "v1"]),
+        Err(UrlError::Missing("filename".to_string()))
+    );
+    assert_eq!(
+        tree.try_url_for(user, &["1", "2", "3"]),
+        Err(UrlError::Extra(2))
+    );
+    assert_eq!(
+        tree.try_url_for(user, &[""]),
+        Err(UrlError::Empty("id".to_string()))
+    );
+    assert_eq!(
+        tree.try_url_for(api, &[""]),
+        Err(UrlError::Empty("+1".to_string()))
+    );
+    assert_eq!(
+        tree.try_url_for(user, &["a/b"]),
+        Err(UrlError::Separator("id".to_string()))
+    );
+    assert_eq!(
+        tree.try_url_for(page, &["a/b"]),
+        Err(UrlError::Separator("page".to_string()))
+    );
+    assert_eq!(
+        UrlError::Separator("id".to_string()).to_string(),
+        "separator in value of parameter `id`"
+    );
+
+    // the generated URLs are matched with the values
+    for (id, params) in [
+        (user, vec!["a b"]),
+        (public, vec!["js/main app.js"]),
+        (api, vec!["v1/users"]),
+    ] {
+        let url = tree.try_url_for(id, &params).unwrap();
+        let (h, p) = tree.find(&url).unwrap();
+        assert_eq!(*h, id);
+        assert_eq!(p.raws.len(), params.len());
+    }
+}
+
+#[test]
+fn brace_expansion() {
+    let mut tree = PathTree::new();
+    let users = tree.insert("/v{1,2}/users/:id", 0);
+    let docs = tree.insert("/{en,fr,de}/docs/*", 1);
+    let apps = tree.insert("/apps/{app_slug}", 2);
+    assert_eq!((users, docs, apps), (0, 1, 2));
+    assert_eq!(tree.iter().count(), 3);
+
+    let (h, p) = tree.find("/v1/users/7").unwrap();
+    assert_eq!((*h, p.alias), (0, None));
+    // the other patterns are variants of the route, not aliases
+    let (h, p) = tree.find("/v2/users/7").unwrap();
+    assert_eq!((*h, *p.id, p.alias), (0, users, None));
+    assert_eq!(p.params(), vec![("id", "7")]);
+    assert!(tree.find("/v3/users/7").is_none());
+    assert!(tree.get_alias(0).is_none());
+    // the nodes of the variants hold tagged values
+    assert_eq!(
+        tree.node.find(b"/v2/users/7").map(|(id, _)| *id),
+        Some(usize::MAX / 4 + 1)
+    );
+
+    for lang in ["en", "fr", "de"] {
+        let path = format!("/{lang}/docs/intro");
+        let (h, p) = tree.find(&path).unwrap();
+        assert_eq!(*h, 1);
+        assert_eq!(p.params(), vec![("*1", "intro")]);
+    }
+    assert!(tree.find("/it/docs/intro").is_none());
+    assert_eq!(tree.find("/apps/{app_slug}").unwrap().0, &2);
+
+    // the URL of the first pattern is generated
+    assert_eq!(tree.url_for(users, &["7"]).unwrap(), "/v1/users/7");
+
+    // a pattern inserted as a route is left to it
+    let mut tree = PathTree::new();
+    let v2 = tree.insert("/v2/users", 0);
+    let users = tree.insert("/{v1,v2}/users", 1);
+    assert_eq!(tree.find("/v1/users").unwrap().0, &1);
+    assert_eq!(*tree.find("/v2/users").unwrap().1.id, v2);
+    assert_ne!(users, v2);
+
+    // inserting the braces again sets the value of the route
+    assert_eq!(tree.insert("/{v1,v2,v3}/users", 2), users);
+    assert_eq!(tree.find("/v3/users").unwrap().0, &2);
+
+    let tree = PathTree::from_sorted([("/{a,b}/:id", 0), ("/c/:id", 1)]);
+    assert_eq!(tree.find("/b/1").unwrap().0, &0);
+    assert_eq!(tree.find("/c/1").unwrap().0, &1);
+}
+
+#[test]
+#[should_panic(
+    expected = "the pattern `/{a,b}/{a,b}/{a,b}/{a,b}/{a,b}/{a,b}/{a,b}/{a,b}/{a,b}` expands to more than 256 patterns"
+)]
+fn brace_expansion_too_many() {
+    let mut tree = PathTree::new();
+    let _ = tree.insert(&"/{a,b}".repeat(9), 0);
+}
+
+#[test]
+fn optional_groups() {
+    let mut tree = PathTree::new().with_optional_groups();
+    let archive = tree.insert("/archive(/:year(/:month))", 0);
+    let report = tree.insert_named("report", "/report(.:format)", 1);
+    tree.insert("/archive/latest", 2);
+    assert_eq!(tree.iter().count(), 3);
+
+    for (path, id, params) in [
+        ("/archive", 0, vec![]),
+        ("/archive/2024", 0, vec![("year", "2024")]),
+        (
+            "/archive/2024/05",
+            0,
+            vec![("year", "2024"), ("month", "05")],
+        ),
+        ("/archive/latest", 2, vec![]),
+        ("/report", 1, vec![]),
+        ("/report.csv", 1, vec![("format", "csv")]),
+    ] {
+        let (h, p) = tree.find(path).unwrap();
+        assert_eq!((*h, p.alias), (id, None), "{path}");
+        assert_eq!(p.params(), params, "{path}");
+    }
+    assert!(tree.find("/archive/2024/05/01").is_none());
+    assert!(tree.find("/archive/").is_none());
+
+    // the absent groups are left out
+    assert_eq!(tree.url_for(archive, &[]).unwrap(), "/archive");
+    assert_eq!(tree.url_for(archive, &["2024"]).unwrap(), "/archive/2024");
+    assert_eq!(
+        tree.url_for(archive, &["2024", ""]).unwrap(),
+        "/archive/2024"
+    );
+    assert_eq!(
+        tree.url_for(archive, &["2024", "05"]).unwrap(),
+        "/archive/2024/05"
+    );
+    assert_eq!(
+        tree.try_url_for(archive, &["2024 1"]).unwrap(),
+        "/archive/2024%201"
+    );
+    assert_eq!(tree.try_url_for(report, &[]).unwrap(), "/report");
+    assert_eq!(
+        tree.url_for_name("report", &[("format", "csv")]).unwrap(),
+        "/report.csv"
+    );
+    assert_eq!(tree.url_for_name("report", &[]).unwrap(), "/report");
+    assert_eq!(
+        tree.url_for_name("report", &[("format", "")]).unwrap(),
+        "/report"
+    );
+
+    let frozen = tree.clone().freeze();
+    for path in [
+        "/archive",
+        "/archive/2024",
+        "/archive/2024/05",
+        "/archive/latest",
+        "/report",
+        "/report.csv",
+        "/reports",
+    ] {
+        assert_eq!(frozen.find(path), tree.find(path), "{path}");
+    }
+
+    // the variants are numbered again with the routes
+    tree.retain(|id, _, _| id != archive);
+    assert!(tree.find("/archive").is_none());
+    assert_eq!(tree.find("/report").unwrap().0, &1);
+    assert_eq!(tree.url_for_name("report", &[]).unwrap(), "/report");
+}
+
+#[test]
+fn variants_and_aliases() {
+    let mut tree = PathTree::new().with_optional_groups();
+    let users = tree.insert_named("user", "/users/:id", 0);
+    assert_eq!(tree.insert_alias("/u/:id/:tab", users), Some(0));
+    assert_eq!(tree.insert_alias("/people", users), Some(1));
+
+    // the aliases are never generated
+    assert_eq!(tree.url_for(users, &["1"]).unwrap(), "/users/1");
+    assert_eq!(tree.url_for(users, &["1", "repos"]).unwrap(), "/users/1");
+    assert_eq!(tree.url_for(users, &[]).unwrap(), "/users/");
+    assert_eq!(
+        tree.try_url_for(users, &[]),
+        Err(UrlError::Missing("id".to_string()))
+    );
+    assert_eq!(
+        tree.try_url_for(users, &["1", "repos"]),
+        Err(UrlError::Extra(1))
+    );
+    assert_eq!(tree.url_for_name("user", &[]).unwrap(), "/users/");
+
+    // the variants are not aliases, and a variant is not taken by an alias
+    let report = tree.insert("/report(.:format)", 1);
+    assert_eq!(tree.get_alias(2), None);
+    assert_eq!(tree.insert_alias("/report", users), None);
+    assert_eq!(tree.insert_alias("/report.:ext", users), None);
+    let (h, p) = tree.find("/report").unwrap();
+    assert_eq!((*h, p.alias), (1, None));
+    assert_eq!(tree.url_for(report, &[]).unwrap(), "/report");
+
+    // an alias is not taken by a variant
+    let archive = tree.insert_alias("/archive", users).unwrap();
+    tree.insert("/archive(/:year)", 2);
+    let (h, p) = tree.find("/archive").unwrap();
+    assert_eq!((*h, p.alias), (0, Some(archive)));
+    assert_eq!(tree.find("/archive/2024").unwrap().0, &2);
+}
+
+#[test]
+fn literal_parentheses() {
+    let routes = [("/wiki/Rust_(language)", 0), ("/report(.:format)", 1)];
+    let mut tree = PathTree::new();
+    for (path, value) in routes {
+        tree.insert(path, value);
+    }
+    let sorted = PathTree::from_sorted(routes);
+
+    for tree in [&tree, &sorted] {
+        let (h, p) = tree.find("/wiki/Rust_(language)").unwrap();
+        assert_eq!((*h, p.alias), (0, None));
+        assert!(tree.find("/wiki/Rust_").is_none());
+        assert!(tree.find("/wiki/Rust_language").is_none());
+
+        assert_eq!(tree.find("/report(.csv)").unwrap().0, &1);
+        assert!(tree.find("/report").is_none());
+        assert!(tree.find("/report.csv").is_none());
+    }
+    assert_eq!(tree.url_for(0, &[]).unwrap(), "/wiki/Rust_(language)");
+}
+
+#[test]
+fn alternations() {
+    let mut tree = PathTree::new();
+    let docs = tree.insert("/:lang(en|fr|de)/docs/*", 0);
+    let report = tree.insert("/report.:format(json|csv)", 1);
+    let page = tree.insert("/:page", 2);
+    let user = tree.insert("/:lang(en|fr)/users/:id", 3);
+    let files = tree.insert("/files/:name.:ext(tar.gz|gz)", 4);
+
+    for (path, id, params) in [
+        (
+            "/en/docs/intro",
+            docs,
+            vec![("lang", "en"), ("*1", "intro")],
+        ),
+        ("/de/docs", docs, vec![("lang", "de"), ("*1", "")]),
+        ("/report.csv", report, vec![("format", "csv")]),
+        ("/fr/users/1", user, vec![("lang", "fr"), ("id", "1")]),
+        (
+            "/files/a.tar.gz",
+            files,
+            vec![("name", "a"), ("ext", "tar.gz")],
+        ),
+        ("/files/a.gz", files, vec![("name", "a"), ("ext", "gz")]),
+        // falls through to the siblings
+        ("/it", page, vec![("page", "it")]),
+        ("/en", page, vec![("page", "en")]),
+        ("/report.xml", page, vec![("page", "report.xml")]),
+    ] {
+        let (h, p) = tree.find(path).unwrap();
+        assert_eq!(*h, id, "{path}");
+        assert_eq!(p.params(), params, "{path}");
+    }
+    for path in ["/it/docs/intro", "/de/users/1", "/files/a.zip"] {
+        assert!(tree.find(path).is_none(), "{path}");
+    }
+
+    let (_, p) = tree.find("/report.json").unwrap();
+    assert_eq!(p.pattern(), "/report.:format(json|csv)");
+
+    assert_eq!(tree.url_for(docs, &["fr", "a/b"]).unwrap(), "/fr/docs/a/b");
+    assert_eq!(tree.try_url_for(report, &["csv"]).unwrap(), "/report.csv");
+    assert_eq!(
+        tree.try_url_for(report, &["xml"]),
+        Err(UrlError::Unlisted("format".to_string()))
+    );
+    assert_eq!(
+        tree.try_url_for(report, &[]),
+        Err(UrlError::Missing("format".to_string()))
+    );
+    assert_eq!(tree.id_of("/:l(en|fr|de)/docs/*"), Some(docs));
+    assert_eq!(tree.id_of("/:l(en|fr)/docs/*"), None);
+
+    let frozen = tree.clone().freeze();
+    let sorted = PathTree::from_sorted([
+        ("/:lang(en|fr|de)/docs/*", 0),
+        ("/report.:format(json|csv)", 1),
+        ("/:page", 2),
+        ("/:lang(en|fr)/users/:id", 3),
+        ("/files/:name.:ext(tar.gz|gz)", 4),
+    ]);
+    for path in [
+        "/en/docs/intro",
+        "/de/docs",
+        "/report.csv",
+        "/report.xml",
+        "/fr/users/1",
+        "/de/users/1",
+        "/files/a.tar.gz",
+        "/it",
+    ] {
+        assert_eq!(frozen.find(path), tree.find(path), "{path}");
+        assert_eq!(sorted.find(path), tree.find(path), "{path}");
+    }
+    assert_eq!(format!("{:?}", sorted.node), format!("{:?}", tree.node));
+
+    tree.retain(|id, _, _| id != docs);
+    assert!(tree.find("/en/docs/intro").is_none());
+    assert_eq!(tree.find("/en/users/1").unwrap().0, &3);
+}
@@ -0,0 +1,207 @@
+#![allow(unused_must_use)]
+
+use path_tree::{Kind, PathTree, Piece};
+use rand::{seq::IndexedRandom, Rng};
+
+#[test]
+fn omitted_segments() {
+    let mut tree = PathTree::new();
+    let posts = tree.insert("/posts/:page?", 0);
+    let date = tree.insert("/api/:day/:month?/:year?", 1);
+    let files = tree.insert("/files/*", 2);
+    let user = tree.insert("/users/:id?/posts", 3);
+    let root = tree.insert("/:lang?", 4);
+
+    for (id, params, url) in [
+        (posts, vec!["2"], "/posts/2"),
+        (posts, vec![""], "/posts"),
+        (posts, vec![], "/posts"),
+        (date, vec!["1", "2", "3"], "/api/1/2/3"),
+        (date, vec!["1", "2"], "/api/1/2"),
+        (date, vec!["1"], "/api/1"),
+        // skipped in the middle by an empty value, the segment is kept
+        (date, vec!["1", "", "3"], "/api/1//3"),
+        (files, vec!["a/b"], "/files/a/b"),
+        (files, vec![], "/files"),
+        (user, vec![""], "/users//posts"),
+        (root, vec![], "/"),
+    ] {
+        assert_eq!(tree.url_for(id, &params).unwrap(), url);
+        assert_eq!(tree.try_url_for(id, &params).unwrap(), url);
+
+        let (h, p) = tree.find(url).unwrap();
+        assert_eq!(*h, id, "{url}");
+        let mut raws = params.clone();
+        raws.resize(p.raws.len(), "");
+        assert_eq!(p.raws.to_vec(), raws, "{url}");
+    }
+
+    // the URLs of both forms are matched
+    let (h, p) = tree.find("/posts/").unwrap();
+    assert_eq!((*h, p.raws.to_vec()), (posts, vec![""]));
+    let (h, p) = tree.find("/files/").unwrap();
+    assert_eq!((*h, p.raws.to_vec()), (files, vec![""]));
+
+    let frozen = tree.clone().freeze();
+    for url in ["/posts", "/api/1", "/api/1/2", "/files"] {
+        assert_eq!(frozen.find(url), tree.find(url), "{url}");
+    }
+}
+
+#[test]
+fn round_trip() {
+    // the values never contain the bytes of the strings, so each URL has one match
+    const SEGMENTS: [&str; 10] = [
+        "/x",
+        "/:a",
+        "/:a.:b",
+        "/:a?",
+        "/v:a?-y",
+        "/zz-:a",
+        "/:a/x",
+        "/:a?/y",
+        "/:a(a|cc)",
+        "/x:a(b1|12).:b",
+    ];
+    const LAST: [&str; 5] = ["/*", "/+", "/x:a*", "/:a?", ""];
+    const VALUES: [&str; 4] = ["a", "b1", "cc", "12"];
+
+    let mut rng = rand::rng();
+
+    for _ in 0..1000 {
+        let mut tree = PathTree::new();
+        let mut routes = Vec::new();
+        for i in 0..4 {
+            let mut route = format!("/r{i}");
+            for _ in 0..rng.random_range(0..4) {
+                route.push_str(SEGMENTS.choose(&mut rng).unwrap());
+            }
+            route.push_str(LAST.choose(&mut rng).unwrap());
+            tree.insert(&route, i);
+            routes.push(route);
+        }
+
+        let frozen = tree.clone().freeze();
+        for (id, route) in routes.iter().enumerate() {
+            let (_, pieces) = tree.get_route(id).unwrap();
+            let params = pieces
+                .iter()
+                .filter_map(|piece| match piece {
+                    Piece::String(_) => None,
+                    Piece::Parameter(_, kind) => Some(Ok(kind)),
+                    Piece::Alternation(_, literals) => Some(Err(literals)),
+                })
+                .map(|kind| match kind {
+                    Err(literals) => {
+                        String::from_utf8(literals.choose(&mut rng).unwrap().clone()).unwrap()
+                    }
+                    Ok(kind) => match kind {
+                        Kind::Normal => VALUES.choose(&mut rng).unwrap().to_string(),
+                        Kind::Optional | Kind::OptionalSegment => {
+                            ["", VALUES.choose(&mut rng).unwrap()]
+                                .choose(&mut rng)
+                                .unwrap()
+                                .to_string()
+                        }
+                        Kind::OneOrMore | Kind::ZeroOrMore | Kind::ZeroOrMoreSegment => {
+                            let n = rng.random_range(usize::from(*kind == Kind::OneOrMore)..3);
+                            (0..n)
+                                .map(|_| *VALUES.choose(&mut rng).unwrap())
+                                .collect::<Vec<_>>()
+                                .join("/")
+                        }
+                    },
+                })
+                .collect::<Vec<_>>();
+            let params = params.iter().map(String::as_str).collect::<Vec<_>>();
+
+            let url = tree.try_url_for(id, &params).unwrap();
+            assert_eq!(tree.url_for(id, &params).unwrap(), url);
+
+            let (h, p) = tree.find(&url).unwrap_or_else(|| panic!("{route} {url}"));
+            assert_eq!((*h, *p.id), (id, id), "{route} {url}");
+            assert_eq!(p.raws.to_vec(), params, "{route} {url}");
+            assert_eq!(frozen.find(&url), tree.find(&url), "{route} {url}");
+        }
+    }
+}
+
+#[test]
+fn query_strings() {
+    let mut tree = PathTree::new();
+    let user = tree.insert("/users/:id", 0);
+    let posts = tree.insert("/posts/:page?", 1);
+
+    assert_eq!(
+        tree.url_for_query(user, &["1"], &[("tab", "repos"), ("q", "a b&c=d")]),
+        Some("/users/1?tab=repos&q=a%20b%26c%3Dd".to_string())
+    );
+    assert_eq!(
+        tree.try_url_for_query(posts, &[], &[("sort", "new")]),
+        Ok("/posts?sort=new".to_string())
+    );
+    assert_eq!(
+        tree.try_url_for_query(user, &["1"], &[]),
+        Ok("/users/1".to_string())
+    );
+    assert_eq!(tree.url_for_query(9, &[], &[("a", "b")]), None);
+    assert!(tree.try_url_for_query(user, &[], &[("a", "b")]).is_err());
+
+    let (h, p) = tree.find_target("/users/1?tab=repos&q=a%20b#top").unwrap();
+    assert_eq!(*h, 0);
+    assert_eq!(p.params(), vec![("id", "1")]);
+    assert_eq!(p.query, Some("tab=repos&q=a%20b"));
+
+    let (_, p) = tree.find_target("/users/1#top?x").unwrap();
+    assert_eq!(p.params(), vec![("id", "1")]);
+    assert_eq!(p.query, None);
+
+    let (h, p) = tree.find_target("/posts?").unwrap();
+    assert_eq!((*h, p.query), (1, Some("")));
+    assert!(tree.find("/users/1?tab=repos").unwrap().1.query.is_none());
+    assert!(tree.find_target("/users?id=1").is_none());
+
+    let url = tree.url_for_query(user, &["2"], &[("x", "1")]).unwrap();
+    let frozen = tree.freeze();
+    let (h, p) = frozen.find_target(&url).unwrap();
+    assert_eq!(*h, 0);
+    assert_eq!(p.params(), vec![("id", "2")]);
+    assert_eq!(p.query, Some("x=1"));
+}
+
+#[test]
+fn url_with() {
+    let mut tree = PathTree::new();
+    tree.insert("/:org/:repo/releases/download/:tag/:filename.:ext", 0);
+    tree.insert("/posts/:category/:page?", 1);
+    tree.insert("/files/*", 2);
+
+    let (_, p) = tree
+        .find("/viz-rs/path-tree/releases/download/v1/path-tree.gz")
+        .unwrap();
+    assert_eq!(
+        p.url_with(&[]),
+        "/viz-rs/path-tree/releases/download/v1/path-tree.gz"
+    );
+    assert_eq!(
+        p.url_with(&[("tag", "v2"), ("ext", "zip"), ("nope", "x")]),
+        "/viz-rs/path-tree/releases/download/v2/path-tree.zip"
+    );
+
+    let (_, p) = tree.find_target("/posts/rust/2?sort=new").unwrap();
+    assert_eq!(p.url_with(&[("page", "3")]), "/posts/rust/3");
+    // an empty segment is left out with its `/`
+    assert_eq!(p.url_with(&[("page", "")]), "/posts/rust");
+
+    let (_, p) = tree.find("/posts/rust").unwrap();
+    assert_eq!(p.url_with(&[]), "/posts/rust");
+    let url = p.url_with(&[("page", "2"), ("category", "go")]);
+    assert_eq!(url, "/posts/go/2");
+    assert_eq!(
+        tree.find(&url).unwrap().1.params(),
+        [("category", "go"), ("page", "2")]
+    );
+
+    let (_, p) = tree.find("/files/a/b").unwrap();
+    assert_eq!(p.url_with(&[("*1", "c")]), "/files/c");
+}